@@ -1,5 +1,4 @@
 use clap::ArgMatches;
-use hyper::client::connect::Connect;
 use tokio::runtime::current_thread::Runtime;
 
 use soma::prelude::*;
@@ -7,19 +6,22 @@ use soma::{Environment, Printer};
 
 pub use self::{
     add::AddCommand, build::BuildCommand, clean::CleanCommand, fetch::FetchCommand,
-    list::ListCommand, remove::RemoveCommand, run::RunCommand, stop::StopCommand,
-    update::UpdateCommand,
+    flag::FlagCommand, info::InfoCommand, list::ListCommand, remove::RemoveCommand,
+    run::RunCommand, stop::StopCommand, update::UpdateCommand, verify::VerifyCommand,
 };
 
 pub mod add;
 pub mod build;
 pub mod clean;
 pub mod fetch;
+pub mod flag;
+pub mod info;
 pub mod list;
 pub mod remove;
 pub mod run;
 pub mod stop;
 pub mod update;
+pub mod verify;
 
 type App = clap::App<'static, 'static>;
 
@@ -27,11 +29,7 @@ pub trait SomaCommand {
     const NAME: &'static str;
 
     fn app(&self) -> App;
-    fn handle_match(
-        &self,
-        env: Environment<impl Connect, impl Printer>,
-        matches: &ArgMatches,
-    ) -> SomaResult<()>;
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()>;
 }
 
 fn default_runtime() -> Runtime {
@@ -1,5 +1,4 @@
 use clap::{Arg, ArgMatches, SubCommand};
-use hyper::client::connect::Connect;
 
 use soma::ops::add;
 use soma::prelude::*;
@@ -37,7 +36,7 @@ impl SomaCommand for AddCommand {
 
     fn handle_match(
         &self,
-        mut env: Environment<impl Connect, impl Printer>,
+        mut env: Environment<impl Printer>,
         matches: &ArgMatches,
     ) -> SomaResult<()> {
         add(
@@ -1,5 +1,4 @@
 use clap::{Arg, ArgMatches, SubCommand};
-use hyper::client::connect::Connect;
 
 use soma::prelude::*;
 use soma::{Environment, Printer};
@@ -28,11 +27,7 @@ impl SomaCommand for BuildCommand {
             )
     }
 
-    fn handle_match(
-        &self,
-        env: Environment<impl Connect, impl Printer>,
-        matches: &ArgMatches,
-    ) -> SomaResult<()> {
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
         build(
             &env,
             matches.value_of("problem").unwrap(),
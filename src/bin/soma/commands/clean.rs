@@ -1,5 +1,4 @@
 use clap::{Arg, ArgMatches, SubCommand};
-use hyper::client::connect::Connect;
 
 use soma::ops::clean;
 use soma::prelude::*;
@@ -28,11 +27,7 @@ impl SomaCommand for CleanCommand {
             )
     }
 
-    fn handle_match(
-        &self,
-        env: Environment<impl Connect, impl Printer>,
-        matches: &ArgMatches,
-    ) -> SomaResult<()> {
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
         clean(
             &env,
             matches.value_of("problem").unwrap(),
@@ -1,13 +1,12 @@
 use std::env::current_dir;
 
 use clap::{Arg, ArgMatches, SubCommand};
-use hyper::client::connect::Connect;
 
 use soma::ops::fetch;
 use soma::prelude::*;
 use soma::{Environment, Printer};
 
-use crate::commands::{App, SomaCommand};
+use crate::commands::{default_runtime, App, SomaCommand};
 
 pub struct FetchCommand;
 
@@ -30,11 +29,12 @@ impl SomaCommand for FetchCommand {
             )
     }
 
-    fn handle_match(
-        &self,
-        env: Environment<impl Connect, impl Printer>,
-        matches: &ArgMatches,
-    ) -> SomaResult<()> {
-        fetch(&env, matches.value_of("problem").unwrap(), current_dir()?)
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
+        fetch(
+            &env,
+            matches.value_of("problem").unwrap(),
+            current_dir()?,
+            &mut default_runtime(),
+        )
     }
 }
@@ -0,0 +1,34 @@
+use clap::{Arg, ArgMatches, SubCommand};
+
+use soma::ops::flag;
+use soma::prelude::*;
+use soma::{Environment, Printer};
+
+use crate::commands::{App, SomaCommand};
+
+pub struct FlagCommand;
+
+impl FlagCommand {
+    pub fn new() -> FlagCommand {
+        FlagCommand {}
+    }
+}
+
+impl SomaCommand for FlagCommand {
+    const NAME: &'static str = "flag";
+
+    fn app(&self) -> App {
+        SubCommand::with_name(Self::NAME)
+            .about("Prints the flag generated for the latest run of a problem")
+            .arg(
+                Arg::with_name("problem")
+                    .required(true)
+                    .help("problem name with optional repository name prefix"),
+            )
+    }
+
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
+        flag(&env, matches.value_of("problem").unwrap())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use clap::{Arg, ArgMatches, SubCommand};
+
+use soma::ops::info;
+use soma::prelude::*;
+use soma::{Environment, Printer};
+
+use crate::commands::{App, SomaCommand};
+
+pub struct InfoCommand;
+
+impl InfoCommand {
+    pub fn new() -> InfoCommand {
+        InfoCommand {}
+    }
+}
+
+impl SomaCommand for InfoCommand {
+    const NAME: &'static str = "info";
+
+    fn app(&self) -> App {
+        SubCommand::with_name(Self::NAME)
+            .about("Shows the description and metadata of a problem")
+            .arg(
+                Arg::with_name("problem")
+                    .required(true)
+                    .help("problem name with optional repository name prefix"),
+            )
+    }
+
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
+        info(&env, matches.value_of("problem").unwrap())?;
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
-use clap::ArgMatches;
 use clap::SubCommand;
-use hyper::client::connect::Connect;
+use clap::{Arg, ArgMatches};
 
 use soma::prelude::*;
+use soma::problem::Metadata;
 use soma::{Environment, Printer};
 
 use crate::commands::{App, SomaCommand};
@@ -19,41 +19,97 @@ impl SomaCommand for ListCommand {
     const NAME: &'static str = "list";
 
     fn app(&self) -> App {
-        SubCommand::with_name(Self::NAME).about("Lists registered repositories")
+        SubCommand::with_name(Self::NAME)
+            .about("Lists registered repositories")
+            .arg(
+                Arg::with_name("category")
+                    .long("category")
+                    .short("c")
+                    .help("lists only problems in the category")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("tag")
+                    .long("tag")
+                    .short("t")
+                    .help("lists only problems with the tag, can be given multiple times")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1),
+            )
     }
 
-    fn handle_match(
-        &self,
-        env: Environment<impl Connect, impl Printer>,
-        _matches: &ArgMatches,
-    ) -> SomaResult<()> {
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
+        let category = matches.value_of("category");
+        let tags: Vec<&str> = matches.values_of("tag").into_iter().flatten().collect();
+        let filtered = category.is_some() || !tags.is_empty();
+
+        // Problems should match the category and every tag
+        let is_listed = |metadata: &Metadata| {
+            category.is_none_or(|category| metadata.has_category(category))
+                && tags.iter().all(|tag| metadata.has_tag(tag))
+        };
+
         let mut repo_iter = env.repo_manager().list_repo().peekable();
+        let mut listed_any = false;
 
         if repo_iter.peek().is_none() {
             env.printer().write_line("No repository was added.");
         } else {
             for repository in repo_iter {
+                let problems: Vec<_> = repository
+                    .prob_metadata_iter()
+                    .filter(|(_, metadata)| is_listed(metadata))
+                    .collect();
+                // Repositories without a matching problem are hidden by filters
+                if filtered && problems.is_empty() {
+                    continue;
+                }
+                listed_any = true;
+
                 env.printer().write_line(&format!(
                     "{} ({})",
                     repository.name(),
                     repository.backend()
                 ));
 
-                let mut peekable = repository.prob_name_iter().peekable();
-                while let Some(name) = peekable.next() {
+                let mut peekable = problems.into_iter().peekable();
+                while let Some((name, metadata)) = peekable.next() {
                     env.printer().write_line(&format!(
-                        "{}─ {}",
+                        "{}─ {}{}",
                         if peekable.peek().is_none() {
                             "└"
                         } else {
                             "├"
                         },
-                        name
+                        name,
+                        summary(metadata)
                     ))
                 }
             }
+
+            if !listed_any {
+                env.printer().write_line("No problem matches the filters.");
+            }
         }
 
         Ok(())
     }
 }
+
+// Category and points are shown next to the problem name when available
+fn summary(metadata: &Metadata) -> String {
+    let points = metadata.points().map(|points| format!("{} points", points));
+    let fields: Vec<String> = metadata
+        .category()
+        .map(str::to_owned)
+        .into_iter()
+        .chain(points)
+        .collect();
+
+    if fields.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", fields.join(", "))
+    }
+}
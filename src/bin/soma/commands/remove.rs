@@ -1,5 +1,4 @@
 use clap::{Arg, ArgMatches, SubCommand};
-use hyper::client::connect::Connect;
 
 use soma::ops::remove;
 use soma::prelude::*;
@@ -30,7 +29,7 @@ impl SomaCommand for RemoveCommand {
 
     fn handle_match(
         &self,
-        mut env: Environment<impl Connect, impl Printer>,
+        mut env: Environment<impl Printer>,
         matches: &ArgMatches,
     ) -> SomaResult<()> {
         remove(
@@ -1,8 +1,8 @@
 use clap::{value_t, Arg, ArgMatches, SubCommand};
-use hyper::client::connect::Connect;
 
-use soma::ops::run;
+use soma::ops::{run, RunOptions};
 use soma::prelude::*;
+use soma::problem::is_valid_env_key;
 use soma::{Environment, Printer};
 
 use crate::commands::{default_runtime, App, SomaCommand};
@@ -28,24 +28,70 @@ impl SomaCommand for RunCommand {
             )
             .arg(
                 Arg::with_name("port")
-                    .required(true)
-                    .help("port number to run the problem"),
+                    .help("port number to run the problem, allocated automatically when omitted"),
+            )
+            .arg(
+                Arg::with_name("env")
+                    .long("env")
+                    .short("e")
+                    .help("environment variable overriding the manifest")
+                    .value_name("KEY=VALUE")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .validator(|variable| match parse_env(&variable) {
+                        Some(_) => Ok(()),
+                        None => Err("expected KEY=VALUE format".to_owned()),
+                    }),
+            )
+            .arg(
+                Arg::with_name("isolated")
+                    .long("isolated")
+                    .help("blocks network access from the problem to outside"),
+            )
+            .arg(
+                Arg::with_name("wait")
+                    .long("wait")
+                    .help("waits until the problem containers become healthy"),
             )
     }
 
-    fn handle_match(
-        &self,
-        env: Environment<impl Connect, impl Printer>,
-        matches: &ArgMatches,
-    ) -> SomaResult<()> {
-        let port = value_t!(matches, "port", u32)?;
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
+        let port = if matches.is_present("port") {
+            Some(value_t!(matches, "port", u16)?)
+        } else {
+            None
+        };
+
+        // Validator guarantees the format
+        let env_overrides = matches
+            .values_of("env")
+            .into_iter()
+            .flatten()
+            .filter_map(parse_env)
+            .collect();
 
         run(
             &env,
             matches.value_of("problem").unwrap(),
-            port,
+            &RunOptions {
+                port,
+                env: env_overrides,
+                isolated: matches.is_present("isolated"),
+                wait: matches.is_present("wait"),
+            },
             &mut default_runtime(),
         )?;
         Ok(())
     }
 }
+
+fn parse_env(variable: &str) -> Option<(String, String)> {
+    let mut split = variable.splitn(2, '=');
+    match (split.next(), split.next()) {
+        (Some(key), Some(value)) if is_valid_env_key(key) => {
+            Some((key.to_owned(), value.to_owned()))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,37 @@
+use clap::{Arg, ArgMatches, SubCommand};
+
+use soma::ops::verify;
+use soma::prelude::*;
+use soma::{Environment, Printer};
+
+use crate::commands::{default_runtime, App, SomaCommand};
+
+pub struct VerifyCommand;
+
+impl VerifyCommand {
+    pub fn new() -> VerifyCommand {
+        VerifyCommand {}
+    }
+}
+
+impl SomaCommand for VerifyCommand {
+    const NAME: &'static str = "verify";
+
+    fn app(&self) -> App {
+        SubCommand::with_name(Self::NAME)
+            .about("Runs the solution of a problem against the problem containers")
+            .arg(
+                Arg::with_name("problem")
+                    .required(true)
+                    .help("problem name with optional repository name prefix"),
+            )
+    }
+
+    fn handle_match(&self, env: Environment<impl Printer>, matches: &ArgMatches) -> SomaResult<()> {
+        verify(
+            &env,
+            matches.value_of("problem").unwrap(),
+            &mut default_runtime(),
+        )
+    }
+}
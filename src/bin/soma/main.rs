@@ -1,7 +1,6 @@
 use std::string::ToString;
 
 use clap::{App, AppSettings};
-use hyper::client::connect::Connect;
 use whoami::username;
 
 use soma::data_dir::DataDirectory;
@@ -15,7 +14,7 @@ use crate::terminal_printer::TerminalPrinter;
 mod commands;
 mod terminal_printer;
 
-fn cli_env(data_dir: &mut DataDirectory) -> SomaResult<Environment<impl Connect, TerminalPrinter>> {
+fn cli_env(data_dir: &mut DataDirectory) -> SomaResult<Environment<TerminalPrinter>> {
     Environment::new(
         username().to_lowercase(),
         data_dir,
@@ -29,11 +28,14 @@ fn main_result() -> SomaResult<()> {
     let build_command = BuildCommand::new();
     let clean_command = CleanCommand::new();
     let fetch_command = FetchCommand::new();
+    let flag_command = FlagCommand::new();
+    let info_command = InfoCommand::new();
     let list_command = ListCommand::new();
     let remove_command = RemoveCommand::new();
     let run_command = RunCommand::new();
     let stop_command = StopCommand::new();
     let update_command = UpdateCommand::new();
+    let verify_command = VerifyCommand::new();
 
     let matches = App::new("soma")
         .version(VERSION)
@@ -43,11 +45,14 @@ fn main_result() -> SomaResult<()> {
         .subcommand(build_command.app())
         .subcommand(clean_command.app())
         .subcommand(fetch_command.app())
+        .subcommand(flag_command.app())
+        .subcommand(info_command.app())
         .subcommand(list_command.app())
         .subcommand(remove_command.app())
         .subcommand(run_command.app())
         .subcommand(stop_command.app())
         .subcommand(update_command.app())
+        .subcommand(verify_command.app())
         .get_matches();
 
     let mut data_dir = DataDirectory::new()?;
@@ -58,11 +63,14 @@ fn main_result() -> SomaResult<()> {
         (BuildCommand::NAME, Some(matches)) => build_command.handle_match(env, matches),
         (CleanCommand::NAME, Some(matches)) => clean_command.handle_match(env, matches),
         (FetchCommand::NAME, Some(matches)) => fetch_command.handle_match(env, matches),
+        (FlagCommand::NAME, Some(matches)) => flag_command.handle_match(env, matches),
+        (InfoCommand::NAME, Some(matches)) => info_command.handle_match(env, matches),
         (ListCommand::NAME, Some(matches)) => list_command.handle_match(env, matches),
         (RemoveCommand::NAME, Some(matches)) => remove_command.handle_match(env, matches),
         (RunCommand::NAME, Some(matches)) => run_command.handle_match(env, matches),
         (StopCommand::NAME, Some(matches)) => stop_command.handle_match(env, matches),
         (UpdateCommand::NAME, Some(matches)) => update_command.handle_match(env, matches),
+        (VerifyCommand::NAME, Some(matches)) => verify_command.handle_match(env, matches),
         _ => unreachable!(),
     }
 }
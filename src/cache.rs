@@ -0,0 +1,110 @@
+use std::io::Write;
+use std::ops::Deref;
+use std::path::PathBuf;
+
+use futures::Stream;
+use hyper::header::LOCATION;
+use hyper::{Body, Client, Uri};
+use hyper_tls::HttpsConnector;
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tokio::runtime::current_thread::Runtime;
+use url::Url;
+
+use crate::data_dir::{DirectoryManager, Registration};
+use crate::prelude::*;
+
+const DNS_WORKER_THREADS: usize = 4;
+const MAX_REDIRECTS: usize = 10;
+pub const SHA256_HEX_LENGTH: usize = 64;
+
+pub fn is_valid_sha256(sha256: &str) -> bool {
+    sha256.len() == SHA256_HEX_LENGTH && sha256.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// Stores remote files of file entries, named after their SHA-256 digests
+pub struct CacheManager<'a> {
+    registration: Registration<'a, CacheManager<'a>>,
+}
+
+impl<'a> Deref for CacheManager<'a> {
+    type Target = Registration<'a, Self>;
+
+    fn deref(&self) -> &<Self as Deref>::Target {
+        &self.registration
+    }
+}
+
+impl<'a> DirectoryManager<'a> for CacheManager<'a> {
+    const DIR: &'static str = "cache";
+
+    fn new(registration: Registration<'a, Self>) -> SomaResult<Self> {
+        Ok(CacheManager { registration })
+    }
+}
+
+impl<'a> CacheManager<'a> {
+    // The digest names the cached file, so it should not be able to point elsewhere
+    fn file_path(&self, sha256: &str) -> SomaResult<PathBuf> {
+        if !is_valid_sha256(sha256) {
+            Err(SomaError::InvalidChecksum)?;
+        }
+        Ok(self.root_path().join(sha256.to_lowercase()))
+    }
+
+    pub fn is_cached(&self, sha256: &str) -> bool {
+        match self.file_path(sha256) {
+            Ok(file_path) => file_path.is_file(),
+            Err(_) => false,
+        }
+    }
+
+    // Downloads and verifies the file unless it is already cached
+    pub fn fetch_file(
+        &self,
+        url: &str,
+        sha256: &str,
+        runtime: &mut Runtime,
+    ) -> SomaResult<PathBuf> {
+        let file_path = self.file_path(sha256)?;
+        if file_path.is_file() {
+            return Ok(file_path);
+        }
+
+        let client = Client::builder().build::<_, Body>(HttpsConnector::new(DNS_WORKER_THREADS)?);
+        let mut url = Url::parse(url)?;
+        let mut response = runtime.block_on(client.get(url.as_str().parse::<Uri>()?))?;
+        for _ in 0..MAX_REDIRECTS {
+            if !response.status().is_redirection() {
+                break;
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .ok_or(SomaError::DownloadFailed)?;
+            url = url.join(location)?;
+            response = runtime.block_on(client.get(url.as_str().parse::<Uri>()?))?;
+        }
+        if !response.status().is_success() {
+            Err(SomaError::DownloadFailed)?;
+        }
+
+        // Partially downloaded files never appear in the cache
+        let mut temp_file = NamedTempFile::new_in(self.root_path())?;
+        let hasher = runtime.block_on(response.into_body().from_err::<failure::Error>().fold(
+            Sha256::new(),
+            |mut hasher, chunk| -> SomaResult<_> {
+                temp_file.write_all(&chunk)?;
+                hasher.input(&chunk);
+                Ok(hasher)
+            },
+        ))?;
+        if format!("{:x}", hasher.result()) != sha256.to_lowercase() {
+            Err(SomaError::ChecksumMismatch)?;
+        }
+
+        temp_file.persist(&file_path)?;
+        Ok(file_path)
+    }
+}
@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::collections::HashSet;
 use std::fs;
 use std::fs::File;
@@ -17,7 +18,7 @@ const LOCK_FILE_NAME: &str = "soma.lock";
 pub struct DataDirectory {
     root_path: PathBuf,
     lock: File,
-    manager_set: HashSet<&'static str>,
+    manager_set: RefCell<HashSet<&'static str>>,
 }
 
 impl DataDirectory {
@@ -53,15 +54,16 @@ impl DataDirectory {
         Ok(DataDirectory {
             root_path: path,
             lock,
-            manager_set: HashSet::new(),
+            manager_set: RefCell::new(HashSet::new()),
         })
     }
 
-    pub fn register<'a, T>(&'a mut self) -> SomaResult<T>
+    // Managers own separate subdirectories, so several can share the borrow
+    pub fn register<'a, T>(&'a self) -> SomaResult<T>
     where
         T: DirectoryManager<'a>,
     {
-        if !self.manager_set.insert(T::DIR) {
+        if !self.manager_set.borrow_mut().insert(T::DIR) {
             panic!("A manager should be registered only once");
         }
 
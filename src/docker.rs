@@ -1,18 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use bollard::container::{
-    APIContainers, Config, CreateContainerOptions, HostConfig, ListContainersOptions, PortBinding,
-    PruneContainersOptions, RemoveContainerOptions, StartContainerOptions, StopContainerOptions,
+    APIContainers, Config, ContainerNetwork, CreateContainerOptions, HostConfig,
+    InspectContainerOptions, ListContainersOptions, LogsOptions, MountPoint,
+    MountPointTmpfsOptions, NetworkingConfig, PortBinding, PruneContainersOptions,
+    RemoveContainerOptions, StartContainerOptions, StopContainerOptions, UploadToContainerOptions,
+    WaitContainerOptions,
 };
+use bollard::errors::DockerResponseNotFoundError;
 use bollard::image::{
     APIImages, BuildImageOptions, ListImagesOptions, PruneImagesOptions, RemoveImageOptions,
 };
-use bollard::Docker;
+use bollard::network::{
+    ConnectNetworkOptions, CreateNetworkOptions, EndpointSettings, InspectNetworkOptions,
+};
+use bollard::{Docker, API_DEFAULT_VERSION};
 use failure::Error;
-use futures::{Future, Stream};
-use hyper::client::connect::Connect;
+use futures::{future, Future, Stream};
+use serde::{Serialize, Serializer};
 
 use crate::prelude::*;
+use crate::problem::configs::{SolidLimitsConfig, SolidSecurityConfig};
 use crate::problem::Problem;
 use crate::{Environment, Printer, VERSION};
 
@@ -21,17 +29,20 @@ const LABEL_KEY_USERNAME: &str = "soma.username";
 const LABEL_KEY_REPOSITORY: &str = "soma.repository";
 const LABEL_KEY_PROBLEM: &str = "soma.problem";
 
+const TMPFS_TARGET: &str = "/tmp";
+const DEFAULT_NETWORK: &str = "bridge";
+
 type DockerLabel<'a> = HashMap<&'a str, &'a str>;
 type SomaFilter = HashMap<String, Vec<String>>;
 
 #[cfg(windows)]
-pub fn connect_default() -> SomaResult<Docker<impl Connect>> {
-    Docker::connect_with_named_pipe("npipe:////./pipe/docker_engine", 600)
+pub fn connect_default() -> SomaResult<Docker> {
+    Docker::connect_with_named_pipe("npipe:////./pipe/docker_engine", 600, API_DEFAULT_VERSION)
 }
 
 #[cfg(unix)]
-pub fn connect_default() -> SomaResult<Docker<impl Connect>> {
-    Docker::connect_with_unix("unix:///var/run/docker.sock", 600)
+pub fn connect_default() -> SomaResult<Docker> {
+    Docker::connect_with_unix("unix:///var/run/docker.sock", 600, API_DEFAULT_VERSION)
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -41,6 +52,15 @@ pub enum VersionStatus {
     NoVersionFound,
 }
 
+// Containers without a healthcheck are considered healthy while running
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HealthStatus {
+    Starting,
+    Healthy,
+    Unhealthy,
+    Exited,
+}
+
 #[derive(Debug)]
 pub struct SomaImage {
     repo_name: String,
@@ -138,6 +158,15 @@ pub fn image_from_prob_exists(images: &[SomaImage], problem: &Problem) -> bool {
     })
 }
 
+pub fn images_from_prob(images: Vec<SomaImage>, problem: &Problem) -> Vec<SomaImage> {
+    images
+        .into_iter()
+        .filter(|image| {
+            image.repo_name() == problem.repo_name() && image.prob_name() == problem.prob_name()
+        })
+        .collect()
+}
+
 pub fn image_from_repo_and_prob_exists(
     images: &[SomaImage],
     repo_name: &str,
@@ -222,7 +251,7 @@ pub fn containers_from_prob(
 }
 
 pub fn list_containers(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
 ) -> impl Future<Item = Vec<SomaContainer>, Error = Error> {
     let soma_filter = SomaFilterBuilder::new().append_user(env.username()).build();
     env.docker
@@ -260,7 +289,7 @@ pub fn list_containers(
 }
 
 pub fn list_images(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
 ) -> impl Future<Item = Vec<SomaImage>, Error = Error> {
     let soma_filter = SomaFilterBuilder::new().append_user(env.username()).build();
     env.docker
@@ -298,12 +327,14 @@ pub fn list_images(
 }
 
 pub fn build<'a>(
-    env: &'a Environment<impl Connect, impl Printer>,
+    env: &'a Environment<impl Printer>,
     labels: DockerLabel<'a>,
     image_name: &'a str,
+    dockerfile: &'a str,
     build_context: Vec<u8>,
 ) -> impl Future<Item = (), Error = Error> + 'a {
     let build_options = BuildImageOptions {
+        dockerfile,
         t: image_name,
         pull: true,
         forcerm: true,
@@ -333,7 +364,7 @@ pub fn build<'a>(
 }
 
 pub fn docker_labels<'a>(
-    env: &'a Environment<impl Connect, impl Printer>,
+    env: &'a Environment<impl Printer>,
     problem: &'a Problem,
 ) -> DockerLabel<'a> {
     vec![
@@ -346,41 +377,432 @@ pub fn docker_labels<'a>(
     .collect()
 }
 
-pub fn create<'a>(
-    env: &'a Environment<impl Connect, impl Printer>,
-    labels: DockerLabel<'a>,
-    image_name: &'a str,
-    port_str: &'a str,
-) -> impl Future<Item = String, Error = Error> + 'a {
+// Container port published to the host, where an empty host port is allocated by docker
+pub struct PortPublish {
+    pub container_port: u16,
+    pub host_port: Option<u16>,
+}
+
+// Settings that differ between containers of a problem
+pub struct ContainerOptions<'a> {
+    pub env: BTreeMap<String, String>,
+    pub port_publish: Option<PortPublish>,
+    pub limits: &'a SolidLimitsConfig,
+    pub security: &'a SolidSecurityConfig,
+    // Contents of the seccomp profile file
+    pub seccomp_profile: Option<String>,
+    // Grants only what creating namespaces needs instead of running privileged
+    pub namespaces: bool,
+}
+
+// Bollard types every value of the container configuration as a string,
+// but docker expects integers for the soft and hard values of ulimits
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct ConfigValue {
+    text: String,
+    integer: Option<u64>,
+}
+
+impl ConfigValue {
+    fn integer(value: u64) -> Self {
+        ConfigValue {
+            text: value.to_string(),
+            integer: Some(value),
+        }
+    }
+}
+
+impl From<String> for ConfigValue {
+    fn from(text: String) -> Self {
+        ConfigValue {
+            text,
+            integer: None,
+        }
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(text: &str) -> Self {
+        ConfigValue::from(text.to_owned())
+    }
+}
+
+impl AsRef<str> for ConfigValue {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Serialize for ConfigValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.integer {
+            Some(value) => serializer.serialize_u64(value),
+            None => serializer.serialize_str(&self.text),
+        }
+    }
+}
+
+fn ulimit(name: &str, value: u64) -> HashMap<ConfigValue, ConfigValue> {
+    vec![
+        ("Name", ConfigValue::from(name)),
+        ("Soft", ConfigValue::integer(value)),
+        ("Hard", ConfigValue::integer(value)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (ConfigValue::from(key), value))
+    .collect()
+}
+
+fn port_bindings(
+    port_publish: &PortPublish,
+) -> HashMap<ConfigValue, Vec<PortBinding<ConfigValue>>> {
+    let host_port = match port_publish.host_port {
+        Some(port) => port.to_string(),
+        None => String::new(),
+    };
     let mut port_bindings = HashMap::new();
     port_bindings.insert(
-        "1337/tcp",
+        ConfigValue::from(port_binding_key(port_publish.container_port)),
         vec![PortBinding {
-            host_ip: "",
-            host_port: port_str,
+            host_ip: ConfigValue::default(),
+            host_port: ConfigValue::from(host_port),
         }],
     );
+    port_bindings
+}
 
-    let host_config = HostConfig {
-        port_bindings: Some(port_bindings),
+fn tmpfs_mount(target: &str, size_bytes: Option<u64>) -> MountPoint<ConfigValue> {
+    MountPoint {
+        target: ConfigValue::from(target),
+        type_: ConfigValue::from("tmpfs"),
+        tmpfs_options: size_bytes.map(|size_bytes| MountPointTmpfsOptions {
+            size_bytes,
+            mode: 0,
+        }),
         ..Default::default()
+    }
+}
+
+// Resource limits and hardening options of the container
+fn restricted_host_config(options: &ContainerOptions) -> HostConfig<ConfigValue> {
+    let limits = options.limits;
+    let security = options.security;
+
+    let ulimits: Vec<_> = vec![("nproc", limits.nproc()), ("fsize", limits.fsize())]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| ulimit(name, value)))
+        .collect();
+
+    // The size limit applies to the scratch directory on the same path
+    let mut tmpfs: BTreeMap<&str, Option<u64>> = security
+        .tmpfs()
+        .iter()
+        .map(|target| (target.as_str(), None))
+        .collect();
+    if let Some(size_bytes) = limits.tmpfs() {
+        tmpfs.insert(TMPFS_TARGET, Some(size_bytes));
+    }
+    let mounts: Vec<_> = tmpfs
+        .into_iter()
+        .map(|(target, size_bytes)| tmpfs_mount(target, size_bytes))
+        .collect();
+
+    let mut security_opt = Vec::new();
+    if security.no_new_privileges() {
+        security_opt.push(ConfigValue::from("no-new-privileges"));
+    }
+    if let Some(profile) = &options.seccomp_profile {
+        security_opt.push(ConfigValue::from(format!("seccomp={}", profile)));
+    }
+
+    let (mut cap_add, cap_drop) = match security.capabilities() {
+        Some(capabilities) => (
+            capabilities
+                .iter()
+                .map(|capability| ConfigValue::from(capability.as_str()))
+                .collect(),
+            Some(vec![ConfigValue::from("ALL")]),
+        ),
+        None => (Vec::new(), None),
     };
 
+    // The default seccomp and AppArmor profiles deny unshare and mount even with SYS_ADMIN
+    if options.namespaces {
+        cap_add.push(ConfigValue::from("SYS_ADMIN"));
+        security_opt.push(ConfigValue::from("seccomp=unconfined"));
+        security_opt.push(ConfigValue::from("apparmor=unconfined"));
+    }
+
+    HostConfig {
+        memory: limits.memory(),
+        // Swap is disabled to make the memory limit a hard limit
+        memory_swap: limits.memory().map(|memory| memory as i64),
+        cpu_shares: limits.cpu_shares(),
+        pids_limit: limits.pids_limit(),
+        ulimits: non_empty(ulimits),
+        mounts: non_empty(mounts),
+        security_opt: non_empty(security_opt),
+        cap_add: non_empty(cap_add),
+        cap_drop,
+        readonly_rootfs: Some(security.read_only()),
+        ..Default::default()
+    }
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+pub fn create<'a>(
+    env: &'a Environment<impl Printer>,
+    labels: DockerLabel<'a>,
+    image_name: &'a str,
+    network_name: &'a str,
+    alias: &'a str,
+    options: ContainerOptions,
+) -> impl Future<Item = String, Error = Error> + 'a {
+    let host_config = HostConfig {
+        port_bindings: options.port_publish.as_ref().map(port_bindings),
+        network_mode: Some(ConfigValue::from(network_name)),
+        ..restricted_host_config(&options)
+    };
+
+    // Other containers in the network reach the container with the alias
+    let mut endpoints_config = HashMap::new();
+    endpoints_config.insert(
+        network_name.to_owned(),
+        ContainerNetwork {
+            ipam_config: None,
+            links: None,
+            aliases: Some(vec![alias.to_owned()]),
+            mac_address: String::new(),
+            global_ipv6_address: String::new(),
+            global_ipv6_prefix_len: 0,
+            ipv6_gateway: String::new(),
+            ip_address: String::new(),
+            ip_prefix_len: 0,
+            gateway: String::new(),
+            endpoint_id: String::new(),
+            network_id: String::new(),
+            driver_opts: None,
+        },
+    );
+
+    let labels = labels
+        .into_iter()
+        .map(|(key, value)| (ConfigValue::from(key), ConfigValue::from(value)))
+        .collect();
+
     env.docker
         .create_container(
             None::<CreateContainerOptions<String>>,
             Config {
-                image: Some(image_name),
+                image: Some(ConfigValue::from(image_name)),
+                env: Some(
+                    options
+                        .env
+                        .into_iter()
+                        .map(|(key, value)| ConfigValue::from(format!("{}={}", key, value)))
+                        .collect(),
+                ),
                 labels: Some(labels),
                 host_config: Some(host_config),
+                networking_config: Some(NetworkingConfig { endpoints_config }),
                 ..Default::default()
             },
         )
         .map(|container_results| container_results.id)
 }
 
+// Forwards the published port from the default bridge network to a container in an
+// internal network, since docker does not publish ports of an internal network
+pub fn create_proxy<'a>(
+    env: &'a Environment<impl Printer>,
+    labels: DockerLabel<'a>,
+    image_name: &'a str,
+    network_name: &'a str,
+    target_alias: &'a str,
+    port_publish: PortPublish,
+) -> impl Future<Item = String, Error = Error> + 'a {
+    let port = port_publish.container_port;
+    let labels = labels
+        .into_iter()
+        .map(|(key, value)| (ConfigValue::from(key), ConfigValue::from(value)))
+        .collect();
+
+    env.docker
+        .create_container(
+            None::<CreateContainerOptions<String>>,
+            Config {
+                image: Some(ConfigValue::from(image_name)),
+                cmd: Some(vec![
+                    ConfigValue::from(format!("TCP-LISTEN:{},fork,reuseaddr", port)),
+                    ConfigValue::from(format!("TCP:{}:{}", target_alias, port)),
+                ]),
+                labels: Some(labels),
+                host_config: Some(HostConfig {
+                    port_bindings: Some(port_bindings(&port_publish)),
+                    network_mode: Some(ConfigValue::from(DEFAULT_NETWORK)),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .and_then(move |container_results| {
+            let container_id = container_results.id;
+            env.docker
+                .connect_network(
+                    network_name,
+                    ConnectNetworkOptions {
+                        container: container_id.clone(),
+                        endpoint_config: EndpointSettings::default(),
+                    },
+                )
+                .map(|_| container_id)
+        })
+}
+
+// Containers in an internal network cannot reach outside of the network
+pub fn create_network(
+    env: &Environment<impl Printer>,
+    labels: DockerLabel,
+    network_name: &str,
+    internal: bool,
+) -> impl Future<Item = String, Error = Error> {
+    let labels = labels
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect();
+
+    env.docker
+        .create_network(CreateNetworkOptions {
+            name: network_name.to_owned(),
+            check_duplicate: true,
+            driver: "bridge".to_owned(),
+            internal,
+            labels,
+            ..Default::default()
+        })
+        .map(|network_results| network_results.id)
+}
+
+// Copies a file into a container, which works before the container starts
+pub fn copy_file<'a>(
+    env: &'a Environment<impl Printer>,
+    container_id: &'a str,
+    file_path: &str,
+    contents: &[u8],
+    mode: u32,
+) -> impl Future<Item = (), Error = Error> + 'a {
+    future::result(file_archive(file_path, contents, mode)).and_then(move |archive| {
+        env.docker.upload_to_container(
+            container_id,
+            Some(UploadToContainerOptions {
+                path: "/".to_owned(),
+                ..Default::default()
+            }),
+            archive.into(),
+        )
+    })
+}
+
+fn file_archive(file_path: &str, contents: &[u8], mode: u32) -> Result<Vec<u8>, Error> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(mode);
+    header.set_cksum();
+
+    let mut archive = tar::Builder::new(Vec::new());
+    archive.append_data(&mut header, file_path.trim_start_matches('/'), contents)?;
+    Ok(archive.into_inner()?)
+}
+
+pub fn bound_host_port(
+    env: &Environment<impl Printer>,
+    container_id: &str,
+    container_port: u16,
+) -> impl Future<Item = u16, Error = Error> {
+    let port_key = port_binding_key(container_port);
+    env.docker
+        .inspect_container(container_id, None::<InspectContainerOptions>)
+        .and_then(move |container| {
+            let host_port = container
+                .network_settings
+                .ports
+                .get(&port_key)
+                .and_then(Option::as_ref)
+                .and_then(|bindings| bindings.first())
+                .and_then(|binding| binding.host_port.parse().ok());
+            match host_port {
+                Some(host_port) => Ok(host_port),
+                None => Err(SomaError::PortBindingNotFound)?,
+            }
+        })
+}
+
+pub fn health_status(
+    env: &Environment<impl Printer>,
+    container_id: &str,
+) -> impl Future<Item = HealthStatus, Error = Error> {
+    env.docker
+        .inspect_container(container_id, None::<InspectContainerOptions>)
+        .map(|container| {
+            let state = container.state;
+            if !state.running {
+                return HealthStatus::Exited;
+            }
+            match state.health {
+                Some(health) => match health.status.as_str() {
+                    "healthy" => HealthStatus::Healthy,
+                    "unhealthy" => HealthStatus::Unhealthy,
+                    _ => HealthStatus::Starting,
+                },
+                None => HealthStatus::Healthy,
+            }
+        })
+}
+
+// Resolves when the container stops running
+pub fn wait_exit(
+    env: &Environment<impl Printer>,
+    container_id: &str,
+) -> impl Future<Item = (), Error = Error> {
+    env.docker
+        .wait_container(container_id, None::<WaitContainerOptions<String>>)
+        .for_each(|_| Ok(()))
+}
+
+// Standard output and error of the container in the order they were written
+pub fn container_logs(
+    env: &Environment<impl Printer>,
+    container_id: &str,
+) -> impl Future<Item = String, Error = Error> {
+    env.docker
+        .logs(
+            container_id,
+            Some(LogsOptions {
+                stdout: true,
+                stderr: true,
+                tail: "all".to_owned(),
+                ..Default::default()
+            }),
+        )
+        .fold(String::new(), |mut output, log| {
+            output.push_str(&log.to_string());
+            Ok::<_, Error>(output)
+        })
+}
+
+fn port_binding_key(container_port: u16) -> String {
+    format!("{}/tcp", container_port)
+}
+
 pub fn remove_image(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     image_name: &str,
 ) -> impl Future<Item = (), Error = Error> {
     env.docker
@@ -389,7 +811,7 @@ pub fn remove_image(
 }
 
 pub fn remove_container(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     container_id: &str,
 ) -> impl Future<Item = (), Error = Error> {
     env.docker
@@ -397,7 +819,7 @@ pub fn remove_container(
 }
 
 pub fn prune_images_from_prob(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     problem: &Problem,
 ) -> impl Future<Item = (), Error = Error> {
     let soma_filter = SomaFilterBuilder::new()
@@ -412,7 +834,7 @@ pub fn prune_images_from_prob(
 }
 
 pub fn prune_containers_from_prob(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     problem: &Problem,
 ) -> impl Future<Item = (), Error = Error> {
     let soma_filter = SomaFilterBuilder::new()
@@ -426,8 +848,37 @@ pub fn prune_containers_from_prob(
         .map(|_| ())
 }
 
+// Whether the network is internal, or none when the network does not exist
+pub fn network_internal(
+    env: &Environment<impl Printer>,
+    network_name: &str,
+) -> impl Future<Item = Option<bool>, Error = Error> {
+    env.docker
+        .inspect_network(network_name, None::<InspectNetworkOptions<&str>>)
+        .map(|network| Some(network.internal))
+        .or_else(
+            |error| match error.downcast::<DockerResponseNotFoundError>() {
+                Ok(_) => Ok(None),
+                Err(error) => Err(error),
+            },
+        )
+}
+
+// Missing network is ignored, as the problem may not have been run with a network
+pub fn remove_network(
+    env: &Environment<impl Printer>,
+    network_name: &str,
+) -> impl Future<Item = (), Error = Error> {
+    env.docker.remove_network(network_name).or_else(|error| {
+        match error.downcast::<DockerResponseNotFoundError>() {
+            Ok(_) => Ok(()),
+            Err(error) => Err(error),
+        }
+    })
+}
+
 pub fn start(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     container_id: &str,
 ) -> impl Future<Item = (), Error = Error> {
     env.docker
@@ -435,7 +886,7 @@ pub fn start(
 }
 
 pub fn stop(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     container_id: &str,
 ) -> impl Future<Item = (), Error = Error> {
     env.docker
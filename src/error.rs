@@ -2,12 +2,26 @@ use failure::Fail;
 
 #[derive(Debug, Fail)]
 pub enum Error {
+    #[fail(display = "The downloaded file does not match the checksum in the manifest")]
+    ChecksumMismatch,
+    #[fail(
+        display = "Container '{}' became unhealthy or exited before it was ready",
+        _0
+    )]
+    ContainerUnhealthy(String),
+    #[fail(
+        display = "Container '{}' did not become healthy within the timeout",
+        _0
+    )]
+    ContainerWaitTimeout(String),
     #[fail(display = "Failed to access the data directory")]
     DataDirectoryAccessDenied,
     #[fail(display = "Another Soma instance is using the data directory")]
     DataDirectoryLockFailed,
     #[fail(display = "Failed to build docker image for a problem")]
     DockerBuildFailed,
+    #[fail(display = "Failed to download a file from the specified URL")]
+    DownloadFailed,
     #[fail(display = "A repository with the same name already exists")]
     DuplicateRepository,
     #[fail(display = "Failed to detect filename from the path")]
@@ -16,8 +30,12 @@ pub enum Error {
         display = "The specified file does not exist, or you don't have enough permission to access it"
     )]
     FileUnreachable,
-    #[fail(display = "Some entry in the manifest is invalid")]
-    InvalidManifest,
+    #[fail(display = "No flag is recorded for the specified problem")]
+    FlagNotFound,
+    #[fail(display = "The checksum is not a SHA-256 digest of hexadecimal digits")]
+    InvalidChecksum,
+    #[fail(display = "Some entry in the manifest is invalid: {}", _0)]
+    InvalidManifest(String),
     #[fail(display = "The provided repository does not contain 'soma.toml' or 'soma-list.toml'")]
     InvalidRepository,
     #[fail(display = "soma-list.toml contains a duplicate or inaccessible entry")]
@@ -28,6 +46,8 @@ pub enum Error {
     InvalidName,
     #[fail(display = "The specified file's path contains unsupported characters")]
     InvalidUnicode,
+    #[fail(display = "Failed to find the host port bound to the problem container")]
+    PortBindingNotFound,
     #[fail(display = "There is a container already running for the specified problem")]
     ProblemAlreadyRunning,
     #[fail(display = "The specified problem is not found")]
@@ -40,6 +60,17 @@ pub enum Error {
     RepositoryInUse,
     #[fail(display = "The specified repository is not found")]
     RepositoryNotFound,
+    #[fail(display = "The solver did not print the expected flag")]
+    SolutionFailed,
+    #[fail(display = "The specified problem does not have a solution")]
+    SolutionNotFound,
+    #[fail(display = "The solver did not finish within the timeout")]
+    SolverTimeout,
+    #[fail(
+        display = "The manifest is written for schema {} while this version of Soma supports up to schema {}; Please update Soma",
+        _0, _1
+    )]
+    UnsupportedSchema(usize, usize),
     #[fail(
         display = "The repository contains changes that cannot be handled by update command; Please remove and add the repository manually"
     )]
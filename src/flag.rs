@@ -0,0 +1,47 @@
+use std::fs;
+use std::ops::Deref;
+use std::path::PathBuf;
+
+use crate::data_dir::{DirectoryManager, Registration};
+use crate::prelude::*;
+use crate::problem::Problem;
+
+// Records the flag generated for the latest run of each problem
+pub struct FlagManager<'a> {
+    registration: Registration<'a, FlagManager<'a>>,
+}
+
+impl<'a> Deref for FlagManager<'a> {
+    type Target = Registration<'a, Self>;
+
+    fn deref(&self) -> &<Self as Deref>::Target {
+        &self.registration
+    }
+}
+
+impl<'a> DirectoryManager<'a> for FlagManager<'a> {
+    const DIR: &'static str = "flags";
+
+    fn new(registration: Registration<'a, Self>) -> SomaResult<Self> {
+        Ok(FlagManager { registration })
+    }
+}
+
+impl<'a> FlagManager<'a> {
+    fn flag_path(&self, problem: &Problem) -> PathBuf {
+        self.root_path().join(problem.fully_qualified_name())
+    }
+
+    pub fn save_flag(&self, problem: &Problem, flag: &str) -> SomaResult<()> {
+        fs::write(self.flag_path(problem), flag)?;
+        Ok(())
+    }
+
+    pub fn load_flag(&self, problem: &Problem) -> SomaResult<String> {
+        let flag_path = self.flag_path(problem);
+        if !flag_path.is_file() {
+            Err(SomaError::FlagNotFound)?;
+        }
+        Ok(fs::read_to_string(flag_path)?)
+    }
+}
@@ -7,20 +7,23 @@ use std::path::Path;
 
 use bollard::Docker;
 use clap::crate_version;
-use hyper::client::connect::Connect;
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::de::{self, Deserializer, Unexpected, Visitor};
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
 
+use crate::cache::CacheManager;
 use crate::data_dir::DataDirectory;
+use crate::flag::FlagManager;
 use crate::prelude::*;
 use crate::repository::RepositoryManager;
 
+pub mod cache;
 pub mod data_dir;
 pub mod docker;
 pub mod error;
+pub mod flag;
 pub mod ops;
 pub mod prelude;
 pub mod problem;
@@ -37,30 +40,36 @@ pub trait Printer {
     fn write_line(&mut self, message: &str);
 }
 
-pub struct Environment<'a, C: 'static, P: Printer + 'static> {
+pub struct Environment<'a, P: Printer + 'static> {
     username: NameString,
     repo_manager: RepositoryManager<'a>,
-    docker: Docker<C>,
+    flag_manager: FlagManager<'a>,
+    cache_manager: CacheManager<'a>,
+    docker: Docker,
     printer: RefCell<P>,
 }
 
-impl<'a, C, P> Environment<'a, C, P>
+impl<'a, P> Environment<'a, P>
 where
-    C: Connect,
     P: Printer,
 {
     pub fn new(
         username: String,
         data_dir: &'a mut DataDirectory,
-        docker: Docker<C>,
+        docker: Docker,
         printer: P,
-    ) -> SomaResult<Environment<'a, C, P>> {
+    ) -> SomaResult<Environment<'a, P>> {
+        let data_dir: &'a DataDirectory = data_dir;
         let repo_manager = data_dir.register::<RepositoryManager>()?;
+        let flag_manager = data_dir.register::<FlagManager>()?;
+        let cache_manager = data_dir.register::<CacheManager>()?;
         let username = NameString::try_from(username)?;
 
         Ok(Environment {
             username,
             repo_manager,
+            flag_manager,
+            cache_manager,
             docker,
             printer: RefCell::new(printer),
         })
@@ -81,6 +90,14 @@ where
     pub fn repo_manager_mut(&mut self) -> &mut RepositoryManager<'a> {
         &mut self.repo_manager
     }
+
+    pub fn flag_manager(&self) -> &FlagManager<'a> {
+        &self.flag_manager
+    }
+
+    pub fn cache_manager(&self) -> &CacheManager<'a> {
+        &self.cache_manager
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -1,25 +1,39 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use fs_extra::{dir, file};
-use futures::Future;
 use handlebars::Handlebars;
-use hyper::client::connect::Connect;
+use serde::Serialize;
 use tempfile::tempdir;
+use tokio::prelude::FutureExt;
 use tokio::runtime::current_thread::Runtime;
 
 use crate::docker;
+use crate::docker::{ContainerOptions, HealthStatus, PortPublish};
 use crate::prelude::*;
-use crate::problem::configs::SolidBinaryConfig;
-use crate::problem::Problem;
+use crate::problem::configs::{
+    FileSource, SolidConfig, SolidDockerConfig, SolidLimitsConfig, SolidSecurityConfig,
+    SolidSolutionConfig, MYSQL_SERVICE_NAME, SOLVER_SERVICE_NAME,
+};
+use crate::problem::{Metadata, Problem, SolidManifest, SolidService};
 use crate::repository::backend;
 use crate::template::{HandleBarsExt, Templates};
 use crate::{Environment, Printer};
 
+const TEMPLATE_DOCKERFILE: &str = "Dockerfile";
+// Readable by the problem user but not writable
+const FLAG_FILE_MODE: u32 = 0o444;
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Shared by every container of a problem, long enough for a database to initialize
+const WAIT_HEALTHY_TIMEOUT: Duration = Duration::from_secs(180);
+
 pub fn add(
-    env: &mut Environment<impl Connect, impl Printer>,
+    env: &mut Environment<impl Printer>,
     repo_location: &str,
     repo_name: Option<&str>,
 ) -> SomaResult<()> {
@@ -41,29 +55,80 @@ pub fn add(
 }
 
 pub fn fetch(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     prob_query: &str,
     cwd: impl AsRef<Path>,
+    runtime: &mut Runtime,
 ) -> SomaResult<()> {
     let problem = env.repo_manager().search_prob(prob_query)?;
     let manifest = problem.load_manifest()?;
 
-    manifest
+    let public_files = manifest
         .public_files()
         .into_iter()
-        .try_for_each(|public_file_path| {
-            let file_path = problem.path().join(public_file_path);
-            let file_name = file_path.file_name().ok_or(SomaError::FileNameNotFound)?;
-
-            env.printer()
-                .write_line(&format!("Fetching '{}'...", file_name.to_string_lossy()));
-            fs::copy(&file_path, cwd.as_ref().join(file_name))?;
-            Ok(())
+        .map(|public_file| {
+            // Fetch-only entries are not checked when the manifest is solidified
+            public_file.validate()?;
+            public_file.expand(problem.path())
         })
+        .collect::<SomaResult<Vec<_>>>()?;
+
+    public_files.iter().flatten().try_for_each(|public_file| {
+        let file_name = public_file.file_name()?;
+        let file_path = resolve_file_source(env, problem.path(), public_file, runtime)?;
+
+        env.printer()
+            .write_line(&format!("Fetching '{}'...", file_name.to_string_lossy()));
+        fs::copy(&file_path, cwd.as_ref().join(file_name))?;
+        Ok(())
+    })
+}
+
+pub fn flag(env: &Environment<impl Printer>, prob_query: &str) -> SomaResult<String> {
+    let problem = env.repo_manager().search_prob(prob_query)?;
+    let flag = env.flag_manager().load_flag(&problem)?;
+    env.printer().write_line(&flag);
+    Ok(flag)
+}
+
+// Metadata comes from the repository index without reading the manifest
+pub fn info(env: &Environment<impl Printer>, prob_query: &str) -> SomaResult<Metadata> {
+    let problem = env.repo_manager().search_prob(prob_query)?;
+    let metadata = problem.metadata();
+
+    env.printer()
+        .write_line(&format!("Problem: {}", problem.fully_qualified_name()));
+    if let Some(category) = metadata.category() {
+        env.printer().write_line(&format!("Category: {}", category));
+    }
+    if let Some(points) = metadata.points() {
+        env.printer().write_line(&format!("Points: {}", points));
+    }
+    if let Some(author) = metadata.author() {
+        env.printer().write_line(&format!("Author: {}", author));
+    }
+    match (metadata.event(), metadata.year()) {
+        (Some(event), Some(year)) => env
+            .printer()
+            .write_line(&format!("Event: {} ({})", event, year)),
+        (Some(event), None) => env.printer().write_line(&format!("Event: {}", event)),
+        (None, Some(year)) => env.printer().write_line(&format!("Year: {}", year)),
+        (None, None) => (),
+    }
+    if !metadata.tags().is_empty() {
+        env.printer()
+            .write_line(&format!("Tags: {}", metadata.tags().join(", ")));
+    }
+    if let Some(description) = metadata.description() {
+        env.printer().write_line("");
+        env.printer().write_line(description.trim_end());
+    }
+
+    Ok(metadata.clone())
 }
 
 pub fn build(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     prob_query: &str,
     runtime: &mut Runtime,
 ) -> SomaResult<()> {
@@ -78,33 +143,77 @@ pub fn build(
     Ok(())
 }
 
-fn construct_image_root(
+// Remote files are replaced with their verified copies in the cache
+fn resolve_file_source(
+    env: &Environment<impl Printer>,
+    problem_dir: impl AsRef<Path>,
+    source: &FileSource,
+    runtime: &mut Runtime,
+) -> SomaResult<PathBuf> {
+    match source {
+        FileSource::Local { path } => Ok(problem_dir.as_ref().join(path)),
+        FileSource::Remote { url, sha256 } => {
+            if !env.cache_manager().is_cached(sha256) {
+                env.printer()
+                    .write_line(&format!("Downloading '{}'...", url));
+            }
+            env.cache_manager().fetch_file(url, sha256, runtime)
+        }
+    }
+}
+
+fn construct_image_root<'a>(
+    env: &Environment<impl Printer>,
     image_root: impl AsRef<Path>,
     problem_dir: impl AsRef<Path>,
-    binary_config: &SolidBinaryConfig,
+    path_maps: impl Iterator<Item = (&'a FileSource, &'a PathBuf)>,
+    runtime: &mut Runtime,
 ) -> SomaResult<()> {
-    let mut dir_copy_options = dir::CopyOptions::new();
-    dir_copy_options.copy_inside = true;
-    // Latter entry has higher priority
-    dir_copy_options.overwrite = true;
-    let mut file_copy_options = file::CopyOptions::new();
-    file_copy_options.overwrite = true;
-
-    for (local_path, target_path) in binary_config.path_maps() {
-        let local_path = problem_dir.as_ref().join(local_path);
+    for (source, target_path) in path_maps {
+        let local_path = resolve_file_source(env, &problem_dir, source, runtime)?;
         let destination = image_root.as_ref().join(target_path.strip_prefix("/")?);
-        // TODO: more descriptive error
-        fs::create_dir_all(destination.parent().ok_or(SomaError::InvalidManifest)?)?;
-        if local_path.is_dir() {
-            if destination.exists() {
-                unimplemented!("Handling copy of nested or duplicate directory");
-            }
-            dir::copy(local_path, destination, &dir_copy_options)?;
-        } else if local_path.is_file() {
-            file::copy(local_path, destination, &file_copy_options)?;
-        } else {
-            Err(SomaError::FileUnreachable)?;
+        let parent = destination.parent().ok_or_else(|| {
+            SomaError::InvalidManifest("file entry has an invalid target path".to_owned())
+        })?;
+        fs::create_dir_all(parent)?;
+        merge_copy(local_path, destination, target_path)?;
+    }
+    Ok(())
+}
+
+// Overlapping directories are merged, and files of a latter entry replace existing ones
+fn merge_copy(
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    target_path: &Path,
+) -> SomaResult<()> {
+    let (source, destination) = (source.as_ref(), destination.as_ref());
+    if source.is_dir() {
+        if destination.is_file() {
+            Err(SomaError::InvalidManifest(format!(
+                "a directory cannot replace a file at '{}'",
+                target_path.display()
+            )))?;
+        }
+        fs::create_dir_all(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            merge_copy(
+                entry.path(),
+                destination.join(entry.file_name()),
+                &target_path.join(entry.file_name()),
+            )?;
+        }
+    } else if source.is_file() {
+        if destination.is_dir() {
+            Err(SomaError::InvalidManifest(format!(
+                "a file cannot replace a directory at '{}'",
+                target_path.display()
+            )))?;
         }
+        fs::copy(source, destination)?;
+    } else {
+        Err(SomaError::FileUnreachable)?;
     }
     Ok(())
 }
@@ -119,29 +228,134 @@ fn encode_context(path: impl AsRef<Path>) -> SomaResult<Vec<u8>> {
 }
 
 fn build_image(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     problem: &Problem,
     runtime: &mut Runtime,
 ) -> SomaResult<()> {
-    let image_name = problem.docker_image_name(env.username());
+    env.printer().write_line("Loading manifest...");
+    let manifest = problem.load_manifest()?.solidify(problem.path())?;
+
+    build_config_image(
+        env,
+        problem,
+        &manifest,
+        &problem.docker_image_name(env.username()),
+        manifest.config(),
+        runtime,
+    )?;
+
+    for service in manifest.services() {
+        env.printer()
+            .write_line(&format!("Building image for service: '{}'", service.name()));
+        build_config_image(
+            env,
+            problem,
+            &manifest.service_context(service),
+            &problem.docker_service_image_name(env.username(), service.name()),
+            service.config(),
+            runtime,
+        )?;
+    }
 
+    if let Some(mysql) = manifest.mysql() {
+        env.printer().write_line("Building MySQL image...");
+        build_service_image(
+            env,
+            problem,
+            &manifest,
+            &problem.docker_service_image_name(env.username(), MYSQL_SERVICE_NAME),
+            Templates::Mysql,
+            mysql.path_maps(),
+            iter::empty(),
+            runtime,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn build_config_image(
+    env: &Environment<impl Printer>,
+    problem: &Problem,
+    template_context: &impl Serialize,
+    image_name: &str,
+    config: &SolidConfig,
+    runtime: &mut Runtime,
+) -> SomaResult<()> {
+    match config {
+        SolidConfig::Docker(docker_config) => {
+            build_dockerfile_image(env, problem, image_name, docker_config, runtime)
+        }
+        _ => build_service_image(
+            env,
+            problem,
+            template_context,
+            image_name,
+            config.templates().ok_or(SomaError::DockerBuildFailed)?,
+            config.path_maps(),
+            config.build_path_maps(),
+            runtime,
+        ),
+    }
+}
+
+// Custom Dockerfile is built from the problem directory without rendering templates
+fn build_dockerfile_image(
+    env: &Environment<impl Printer>,
+    problem: &Problem,
+    image_name: &str,
+    docker_config: &SolidDockerConfig,
+    runtime: &mut Runtime,
+) -> SomaResult<()> {
+    let context_path = problem.path().join(docker_config.context());
+    if !context_path.is_dir() {
+        Err(SomaError::FileUnreachable)?;
+    }
+
+    env.printer().write_line("Encoding build context...");
+    let build_context = encode_context(context_path)?;
+
+    env.printer().write_line("Building image...");
+    let labels = docker::docker_labels(env, problem);
+    runtime.block_on(docker::build(
+        env,
+        labels,
+        image_name,
+        docker_config.dockerfile(),
+        build_context,
+    ))?;
+    Ok(())
+}
+
+// Build sources are kept out of the image root since only the builder stage copies them
+#[allow(clippy::too_many_arguments)]
+fn build_service_image<'a>(
+    env: &Environment<impl Printer>,
+    problem: &Problem,
+    template_context: &impl Serialize,
+    image_name: &str,
+    templates: Templates,
+    path_maps: impl Iterator<Item = (&'a FileSource, &'a PathBuf)>,
+    build_path_maps: impl Iterator<Item = (&'a FileSource, &'a PathBuf)>,
+    runtime: &mut Runtime,
+) -> SomaResult<()> {
     env.printer().write_line("Preparing build context...");
     let context = tempdir()?;
     let context_path = context.path();
 
-    env.printer().write_line("Loading manifest...");
-    let manifest = problem.load_manifest()?.solidify()?;
-
     env.printer().write_line("Constructing image root...");
     let image_root = context_path.join("image-root");
     let problem_dir = problem.path();
     fs::create_dir(&image_root)?;
-    let binary_config = manifest.binary();
-    construct_image_root(image_root, problem_dir, binary_config)?;
+    construct_image_root(env, image_root, problem_dir, path_maps, runtime)?;
+
+    let build_root = context_path.join("build-root");
+    fs::create_dir(&build_root)?;
+    construct_image_root(env, build_root, problem_dir, build_path_maps, runtime)?;
 
     env.printer().write_line("Rendering build files...");
     fs::create_dir(context_path.join(".soma"))?;
-    Handlebars::new().render_templates(Templates::Binary, &manifest, context_path)?;
+    Handlebars::new().render_templates(templates, template_context, context_path)?;
 
     env.printer().write_line("Encoding build context...");
     let build_context = encode_context(context_path)?;
@@ -149,47 +363,337 @@ fn build_image(
     context.close()?;
     env.printer().write_line("Building image...");
     let labels = docker::docker_labels(&env, &problem);
-    runtime.block_on(docker::build(&env, labels, &image_name, build_context))?;
+    runtime.block_on(docker::build(
+        &env,
+        labels,
+        image_name,
+        TEMPLATE_DOCKERFILE,
+        build_context,
+    ))?;
     Ok(())
 }
 
+#[derive(Default)]
+pub struct RunOptions {
+    // Host port of the main service, allocated automatically when omitted
+    pub port: Option<u16>,
+    // Overrides environment variables of every service in the manifest
+    pub env: BTreeMap<String, String>,
+    // Disconnects the problem from outside even if the manifest does not
+    pub isolated: bool,
+    // Returns after every container passes its healthcheck
+    pub wait: bool,
+}
+
 pub fn run(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     prob_query: &str,
-    port: u32,
+    options: &RunOptions,
     runtime: &mut Runtime,
-) -> SomaResult<String> {
+) -> SomaResult<(String, u16)> {
     let problem = env.repo_manager().search_prob(prob_query)?;
-    let image_name = problem.docker_image_name(env.username());
-    let port_str = &port.to_string();
+    let manifest = problem.load_manifest()?.solidify(problem.path())?;
 
     let containers = runtime.block_on(docker::list_containers(&env))?;
     if docker::container_from_prob_running(&containers, &problem) {
         Err(SomaError::ProblemAlreadyRunning)?
     }
 
+    let network_name = problem.docker_network_name(env.username());
     runtime.block_on(docker::prune_containers_from_prob(&env, &problem))?;
 
-    let labels = docker::docker_labels(env, &problem);
-    let container_run =
-        docker::create(env, labels, &image_name, port_str).and_then(|container_name| {
-            env.printer().write_line("Starting container...");
-            docker::start(env, &container_name).map(|_| container_name)
-        });
-
     env.printer().write_line(&format!(
-        "Creating container for problem: '{}'",
+        "Creating containers for problem: '{}'",
         problem.fully_qualified_name()
     ));
-    let container_name = runtime.block_on(container_run)?;
+    let mut container_names = Vec::new();
+    let result = run_containers(
+        env,
+        &problem,
+        &manifest,
+        &network_name,
+        options,
+        &mut container_names,
+        runtime,
+    );
+
+    // Containers of a problem are started and stopped as a group
+    if result.is_err() {
+        env.printer()
+            .write_line("Rolling back started containers...");
+        for container_name in &container_names {
+            let _ = runtime.block_on(docker::stop(env, container_name));
+            let _ = runtime.block_on(docker::remove_container(env, container_name));
+        }
+    }
+
+    result
+}
+
+// Docker expects the contents of the profile instead of its path
+fn seccomp_profile(problem: &Problem, service: &SolidService) -> SomaResult<Option<String>> {
+    match service.security().seccomp() {
+        Some(path) => {
+            let profile_path = problem.path().join(path);
+            if !profile_path.is_file() {
+                Err(SomaError::FileUnreachable)?;
+            }
+            Ok(Some(fs::read_to_string(profile_path)?))
+        }
+        None => Ok(None),
+    }
+}
+
+fn run_containers(
+    env: &Environment<impl Printer>,
+    problem: &Problem,
+    manifest: &SolidManifest,
+    network_name: &str,
+    options: &RunOptions,
+    container_names: &mut Vec<String>,
+    runtime: &mut Runtime,
+) -> SomaResult<(String, u16)> {
+    let isolated = manifest.isolated() || options.isolated;
+    let proxy_image_name = problem.docker_proxy_image_name(env.username());
+    // The proxy image does not depend on the manifest, so it is built only once
+    if isolated
+        && !docker::image_exists(
+            &runtime.block_on(docker::list_images(env))?,
+            &proxy_image_name,
+        )
+    {
+        env.printer().write_line("Building proxy image...");
+        build_service_image(
+            env,
+            problem,
+            &(),
+            &proxy_image_name,
+            Templates::Proxy,
+            iter::empty(),
+            iter::empty(),
+            runtime,
+        )?;
+    }
+
+    // The network is kept between runs unless the isolation changed
+    match runtime.block_on(docker::network_internal(env, network_name))? {
+        Some(internal) if internal == isolated => (),
+        existing => {
+            if existing.is_some() {
+                runtime.block_on(docker::remove_network(env, network_name))?;
+            }
+            let labels = docker::docker_labels(env, problem);
+            runtime.block_on(docker::create_network(env, labels, network_name, isolated))?;
+        }
+    }
+
+    // Returns the container and the container which publishes its port
+    let mut run_container = |image_name: &str,
+                             alias: &str,
+                             mut options: ContainerOptions,
+                             flag_file: Option<(String, &str)>|
+     -> SomaResult<(String, String)> {
+        let proxy_publish = if isolated {
+            options.port_publish.take()
+        } else {
+            None
+        };
+
+        let labels = docker::docker_labels(env, problem);
+        let container_name = runtime.block_on(docker::create(
+            env,
+            labels,
+            image_name,
+            network_name,
+            alias,
+            options,
+        ))?;
+        container_names.push(container_name.clone());
+        // The flag is never written to an image layer
+        if let Some((flag_path, flag)) = flag_file {
+            runtime.block_on(docker::copy_file(
+                env,
+                &container_name,
+                &flag_path,
+                flag.as_bytes(),
+                FLAG_FILE_MODE,
+            ))?;
+        }
+        runtime.block_on(docker::start(env, &container_name))?;
+
+        match proxy_publish {
+            Some(port_publish) => {
+                let labels = docker::docker_labels(env, problem);
+                let proxy_name = runtime.block_on(docker::create_proxy(
+                    env,
+                    labels,
+                    &proxy_image_name,
+                    network_name,
+                    alias,
+                    port_publish,
+                ))?;
+                container_names.push(proxy_name.clone());
+                runtime.block_on(docker::start(env, &proxy_name))?;
+                Ok((container_name, proxy_name))
+            }
+            None => Ok((container_name.clone(), container_name)),
+        }
+    };
+
+    // Services reach the database with its service name as a host name
+    let mut mysql_env = BTreeMap::new();
+    if let Some(mysql) = manifest.mysql() {
+        env.printer().write_line("Starting MySQL container...");
+        run_container(
+            &problem.docker_service_image_name(env.username(), MYSQL_SERVICE_NAME),
+            MYSQL_SERVICE_NAME,
+            ContainerOptions {
+                env: mysql.server_env(),
+                port_publish: None,
+                limits: &SolidLimitsConfig::default(),
+                security: &SolidSecurityConfig::default(),
+                seccomp_profile: None,
+                namespaces: false,
+            },
+            None,
+        )?;
+        mysql_env = mysql.client_env();
+    }
+
+    // Manifest overrides database variables, and run options override both
+    let service_env = |service: &SolidService| -> BTreeMap<String, String> {
+        let mut container_env = mysql_env.clone();
+        container_env.extend(service.env().clone());
+        container_env.extend(options.env.clone());
+        container_env
+    };
+
+    let mut service_ports = Vec::new();
+    for service in manifest.services() {
+        env.printer().write_line(&format!(
+            "Starting container for service: '{}'",
+            service.name()
+        ));
+        let container_port = service.config().port();
+        let port_publish = if service.exposed() {
+            Some(PortPublish {
+                container_port,
+                host_port: None,
+            })
+        } else {
+            None
+        };
+
+        let (_, publisher_name) = run_container(
+            &problem.docker_service_image_name(env.username(), service.name()),
+            service.name(),
+            ContainerOptions {
+                env: service_env(service),
+                port_publish,
+                limits: service.limits(),
+                security: service.security(),
+                seccomp_profile: seccomp_profile(problem, service)?,
+                namespaces: service.config().creates_namespaces(),
+            },
+            None,
+        )?;
+        if service.exposed() {
+            service_ports.push((service.name(), publisher_name, container_port));
+        }
+    }
+
+    env.printer().write_line("Starting container...");
+    let main_service = manifest.main_service();
+    let container_port = main_service.config().port();
+    let flag = manifest
+        .flag()
+        .map(|flag_config| (flag_config, flag_config.generate()));
+    let flag_file = flag.as_ref().map(|(flag_config, flag)| {
+        let root_dir = main_service.config().root_dir().trim_end_matches('/');
+        (format!("{}{}", root_dir, flag_config.path()), flag.as_str())
+    });
+    let (container_name, publisher_name) = run_container(
+        &problem.docker_image_name(env.username()),
+        main_service.name(),
+        ContainerOptions {
+            env: service_env(main_service),
+            port_publish: Some(PortPublish {
+                container_port,
+                host_port: options.port,
+            }),
+            limits: main_service.limits(),
+            security: main_service.security(),
+            seccomp_profile: seccomp_profile(problem, main_service)?,
+            namespaces: main_service.config().creates_namespaces(),
+        },
+        flag_file,
+    )?;
     env.printer()
         .write_line(&format!("Container started: '{}'", &container_name));
 
-    Ok(container_name)
+    if options.wait {
+        env.printer()
+            .write_line("Waiting for containers to become healthy...");
+        wait_healthy(env, container_names, runtime)?;
+    }
+
+    if let Some((flag_config, flag)) = &flag {
+        env.flag_manager().save_flag(problem, flag)?;
+        env.printer()
+            .write_line(&format!("Flag generated at: '{}'", flag_config.path()));
+    }
+
+    for (service_name, service_publisher_name, service_container_port) in service_ports {
+        let host_port = runtime.block_on(docker::bound_host_port(
+            env,
+            &service_publisher_name,
+            service_container_port,
+        ))?;
+        env.printer().write_line(&format!(
+            "Service '{}' is available at port: {}",
+            service_name, host_port
+        ));
+    }
+
+    let host_port = runtime.block_on(docker::bound_host_port(
+        env,
+        &publisher_name,
+        container_port,
+    ))?;
+    env.printer()
+        .write_line(&format!("Problem is available at port: {}", host_port));
+
+    Ok((container_name, host_port))
+}
+
+// Docker marks a container unhealthy after the retries of its healthcheck run out
+fn wait_healthy(
+    env: &Environment<impl Printer>,
+    container_names: &[String],
+    runtime: &mut Runtime,
+) -> SomaResult<()> {
+    let deadline = Instant::now() + WAIT_HEALTHY_TIMEOUT;
+    for container_name in container_names {
+        loop {
+            match runtime.block_on(docker::health_status(env, container_name))? {
+                HealthStatus::Healthy => break,
+                HealthStatus::Starting => {
+                    if Instant::now() >= deadline {
+                        Err(SomaError::ContainerWaitTimeout(container_name.clone()))?;
+                    }
+                    thread::sleep(HEALTH_POLL_INTERVAL)
+                }
+                HealthStatus::Unhealthy | HealthStatus::Exited => {
+                    Err(SomaError::ContainerUnhealthy(container_name.clone()))?
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 pub fn remove(
-    env: &mut Environment<impl Connect, impl Printer>,
+    env: &mut Environment<impl Printer>,
     repo_name: &str,
     runtime: &mut Runtime,
 ) -> SomaResult<()> {
@@ -206,7 +710,7 @@ pub fn remove(
 }
 
 pub fn clean(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     prob_query: &str,
     runtime: &mut Runtime,
 ) -> SomaResult<()> {
@@ -221,6 +725,18 @@ pub fn clean(
         env,
         &problem.docker_image_name(env.username()),
     ))?;
+
+    // Sidecar images carry the same problem labels
+    let image_list =
+        docker::images_from_prob(runtime.block_on(docker::list_images(env))?, &problem);
+    for image in image_list {
+        runtime.block_on(docker::remove_image(env, &image.image().id))?;
+    }
+    // The network is reused by later runs until the problem is cleaned
+    runtime.block_on(docker::remove_network(
+        env,
+        &problem.docker_network_name(env.username()),
+    ))?;
     env.printer().write_line(&format!(
         "Problem image cleaned: '{}'",
         problem.fully_qualified_name()
@@ -230,7 +746,7 @@ pub fn clean(
 }
 
 pub fn stop(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     prob_query: &str,
     runtime: &mut Runtime,
 ) -> SomaResult<()> {
@@ -264,8 +780,111 @@ pub fn stop(
     Ok(())
 }
 
+// Containers of the problem and the solver are removed whether the solution passes or not
+pub fn verify(
+    env: &Environment<impl Printer>,
+    prob_query: &str,
+    runtime: &mut Runtime,
+) -> SomaResult<()> {
+    let problem = env.repo_manager().search_prob(prob_query)?;
+    let manifest = problem.load_manifest()?.solidify(problem.path())?;
+    let solution = manifest.solution().ok_or(SomaError::SolutionNotFound)?;
+
+    build(env, prob_query, runtime)?;
+    let options = RunOptions {
+        wait: true,
+        ..Default::default()
+    };
+    run(env, prob_query, &options, runtime)?;
+
+    // The outcome of the solver takes precedence over a failure to stop the problem
+    let result = run_solver(env, &problem, &manifest, solution, runtime);
+    let cleanup = stop(env, prob_query, runtime);
+    if let (Err(_), Err(error)) = (&result, &cleanup) {
+        env.printer()
+            .write_line(&format!("Failed to stop the problem: {}", error));
+    }
+    result?;
+
+    env.printer().write_line(&format!(
+        "Solution verified: '{}'",
+        problem.fully_qualified_name()
+    ));
+    cleanup
+}
+
+fn run_solver(
+    env: &Environment<impl Printer>,
+    problem: &Problem,
+    manifest: &SolidManifest,
+    solution: &SolidSolutionConfig,
+    runtime: &mut Runtime,
+) -> SomaResult<()> {
+    env.printer().write_line("Building solver image...");
+    let image_name = problem.docker_solver_image_name(env.username());
+    build_service_image(
+        env,
+        problem,
+        solution,
+        &image_name,
+        Templates::Solver,
+        iter::once(solution.path_map()),
+        iter::empty(),
+        runtime,
+    )?;
+
+    // The solver connects to the main service inside the problem network
+    let main_service = manifest.main_service();
+    let mut solver_env = BTreeMap::new();
+    solver_env.insert("TARGET_HOST".to_owned(), main_service.name().to_string());
+    solver_env.insert(
+        "TARGET_PORT".to_owned(),
+        main_service.config().port().to_string(),
+    );
+
+    env.printer().write_line("Running solver...");
+    let labels = docker::docker_labels(env, problem);
+    let container_name = runtime.block_on(docker::create(
+        env,
+        labels,
+        &image_name,
+        &problem.docker_network_name(env.username()),
+        SOLVER_SERVICE_NAME,
+        ContainerOptions {
+            env: solver_env,
+            port_publish: None,
+            limits: &SolidLimitsConfig::default(),
+            security: &SolidSecurityConfig::default(),
+            seccomp_profile: None,
+            namespaces: false,
+        },
+    ))?;
+    runtime.block_on(docker::start(env, &container_name))?;
+
+    let timeout = Duration::from_secs(solution.timeout());
+    if let Err(error) = runtime.block_on(docker::wait_exit(env, &container_name).timeout(timeout)) {
+        match error.into_inner() {
+            Some(error) => Err(error)?,
+            // The deadline elapsed before the solver exited
+            None => Err(SomaError::SolverTimeout)?,
+        }
+    }
+
+    let output = runtime.block_on(docker::container_logs(env, &container_name))?;
+    env.printer().write_line(output.trim_end());
+
+    let generated_flag = match manifest.flag() {
+        Some(_) => Some(env.flag_manager().load_flag(problem)?),
+        None => None,
+    };
+    if !solution.output_matches(&output, generated_flag.as_deref()) {
+        Err(SomaError::SolutionFailed)?;
+    }
+    Ok(())
+}
+
 pub fn update(
-    env: &Environment<impl Connect, impl Printer>,
+    env: &Environment<impl Printer>,
     repo_name: &str,
     runtime: &mut Runtime,
 ) -> SomaResult<()> {
@@ -1,28 +1,65 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use toml::value::{Table, Value};
 
-use self::configs::{BinaryConfig, SolidBinaryConfig};
+use self::configs::{
+    ApachePhp7Config, BinaryConfig, DockerConfig, FileSource, FlagConfig, HealthcheckConfig,
+    LimitsConfig, MysqlConfig, PythonUwsgiConfig, SecurityConfig, SolidConfig, SolidFlagConfig,
+    SolidHealthcheckConfig, SolidLimitsConfig, SolidMysqlConfig, SolidSecurityConfig,
+    SolidSolutionConfig, SolutionConfig, MYSQL_SERVICE_NAME, SOLVER_SERVICE_NAME,
+};
 use crate::prelude::*;
+use crate::repository::read_list_defaults;
 use crate::{read_file_contents, NameString};
 
+pub use self::metadata::Metadata;
+
 pub mod configs;
+mod metadata;
+mod schema;
 
 pub const MANIFEST_FILE_NAME: &str = "soma.toml";
 
+// Keys identifying each problem, which cannot be shared through the defaults
+const UNINHERITABLE_KEYS: &[&str] = &["name", "services"];
+// Defaults of these sections apply only to manifests containing the section
+const OPTIONAL_SECTIONS: &[&str] = &[
+    "binary",
+    "apache-php7",
+    "python-uwsgi",
+    "docker",
+    "mysql",
+    "flag",
+    "healthcheck",
+    "solution",
+];
+
 #[derive(Debug)]
 pub struct Problem {
     repo_name: NameString,
     prob_name: NameString,
+    repo_path: PathBuf,
     path: PathBuf,
+    metadata: Metadata,
 }
 
 impl Problem {
-    pub fn new(repo_name: NameString, prob_name: NameString, path: PathBuf) -> Self {
+    pub fn new(
+        repo_name: NameString,
+        prob_name: NameString,
+        repo_path: PathBuf,
+        path: PathBuf,
+        metadata: Metadata,
+    ) -> Self {
         Problem {
             repo_name,
             prob_name,
+            repo_path,
             path,
+            metadata,
         }
     }
 
@@ -34,6 +71,23 @@ impl Problem {
         format!("soma.{}/{}", user_name, self.fully_qualified_name())
     }
 
+    pub fn docker_network_name(&self, user_name: &str) -> String {
+        format!("soma.{}.{}", user_name, self.fully_qualified_name())
+    }
+
+    pub fn docker_service_image_name(&self, user_name: &str, service_name: &str) -> String {
+        format!("{}.{}", self.docker_image_name(user_name), service_name)
+    }
+
+    // Names cannot contain a slash, so the proxy image never collides with a service image
+    pub fn docker_proxy_image_name(&self, user_name: &str) -> String {
+        format!("{}/proxy", self.docker_image_name(user_name))
+    }
+
+    pub fn docker_solver_image_name(&self, user_name: &str) -> String {
+        format!("{}/solver", self.docker_image_name(user_name))
+    }
+
     pub fn repo_name(&self) -> &NameString {
         &self.repo_name
     }
@@ -46,24 +100,106 @@ impl Problem {
         &self.path
     }
 
+    // Metadata recorded in the repository index when the repository was added
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    // Problems inherit the defaults of the repository's problem list
     pub fn load_manifest(&self) -> SomaResult<Manifest> {
         let manifest_path = self.path().join(MANIFEST_FILE_NAME);
-        read_manifest(manifest_path)
+        read_manifest(manifest_path, &read_list_defaults(&self.repo_path)?)
     }
 }
 
 #[derive(Deserialize)]
 pub struct Manifest {
     name: NameString,
+    #[serde(flatten)]
+    metadata: Metadata,
+    work_dir: Option<PathBuf>,
+    #[serde(default)]
+    isolated: bool,
+    #[serde(flatten)]
+    subconfigs: Subconfigs,
+    mysql: Option<MysqlConfig>,
+    flag: Option<FlagConfig>,
+    solution: Option<SolutionConfig>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    limits: LimitsConfig,
+    #[serde(default)]
+    security: SecurityConfig,
+    healthcheck: Option<HealthcheckConfig>,
+    #[serde(default)]
+    services: BTreeMap<NameString, ServiceConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct ServiceConfig {
     work_dir: Option<PathBuf>,
-    binary: BinaryConfig,
+    #[serde(default)]
+    exposed: bool,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    limits: LimitsConfig,
+    #[serde(default)]
+    security: SecurityConfig,
+    healthcheck: Option<HealthcheckConfig>,
+    #[serde(flatten)]
+    subconfigs: Subconfigs,
 }
 
+#[derive(Deserialize)]
+struct Subconfigs {
+    binary: Option<BinaryConfig>,
+    #[serde(rename = "apache-php7")]
+    apache_php7: Option<ApachePhp7Config>,
+    #[serde(rename = "python-uwsgi")]
+    python_uwsgi: Option<PythonUwsgiConfig>,
+    docker: Option<DockerConfig>,
+}
+
+// Serialized in the same shape for every service to share templates
 #[derive(Serialize)]
-pub struct SolidManifest {
+pub struct SolidService {
     name: NameString,
     work_dir: PathBuf,
-    binary: SolidBinaryConfig,
+    #[serde(skip)]
+    exposed: bool,
+    #[serde(skip)]
+    env: BTreeMap<String, String>,
+    #[serde(skip)]
+    limits: SolidLimitsConfig,
+    #[serde(skip)]
+    security: SolidSecurityConfig,
+    healthcheck: Option<SolidHealthcheckConfig>,
+    #[serde(flatten)]
+    config: SolidConfig,
+}
+
+#[derive(Serialize)]
+pub struct SolidManifest {
+    #[serde(flatten)]
+    main_service: SolidService,
+    mysql: Option<SolidMysqlConfig>,
+    #[serde(skip)]
+    services: Vec<SolidService>,
+    #[serde(skip)]
+    isolated: bool,
+    #[serde(skip)]
+    flag: Option<SolidFlagConfig>,
+    #[serde(skip)]
+    solution: Option<SolidSolutionConfig>,
+}
+
+#[derive(Serialize)]
+pub struct ServiceContext<'a> {
+    #[serde(flatten)]
+    service: &'a SolidService,
+    mysql: Option<&'a SolidMysqlConfig>,
 }
 
 impl Manifest {
@@ -71,45 +207,1161 @@ impl Manifest {
         &self.name
     }
 
-    pub fn public_files(&self) -> Vec<&PathBuf> {
-        let binary = &self.binary;
-        let executables = binary.executable().iter();
-        let readonly = binary.readonly().iter();
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn public_files(&self) -> Vec<&FileSource> {
+        let services = self
+            .services
+            .values()
+            .flat_map(|service| service.subconfigs.public_files());
 
-        executables
-            .chain(readonly)
-            .filter(|file_entry| file_entry.public())
-            .map(|file_entry| file_entry.path())
+        self.subconfigs
+            .public_files()
+            .into_iter()
+            .chain(services)
             .collect()
     }
 
-    pub fn solidify(&self) -> SomaResult<SolidManifest> {
-        let work_dir = match &self.work_dir {
-            Some(path) => path.clone(),
-            None => PathBuf::from(format!("/home/{}", self.name)),
-        };
+    // Glob patterns of file entries are matched against the problem directory
+    pub fn solidify(&self, problem_dir: impl AsRef<Path>) -> SomaResult<SolidManifest> {
+        let main_service = solidify_service(
+            &problem_dir,
+            &self.name,
+            &self.work_dir,
+            true,
+            &self.env,
+            &self.limits,
+            &self.security,
+            &self.healthcheck,
+            &self.subconfigs,
+        )?;
 
-        // TODO: More descriptive error
-        if !work_dir.has_root() {
-            Err(SomaError::InvalidManifest)?;
+        // Service names are used as host names inside the problem network
+        if self.services.contains_key(&self.name) {
+            Err(SomaError::InvalidManifest(format!(
+                "service '{}' has the same name as the problem",
+                self.name
+            )))?;
         }
 
-        let binary = self.binary.solidify(&work_dir)?;
+        // The database runs as a sidecar next to the main subconfiguration
+        let mysql = match &self.mysql {
+            Some(mysql) => {
+                if self.name == *MYSQL_SERVICE_NAME
+                    || self.services.keys().any(|name| name == MYSQL_SERVICE_NAME)
+                {
+                    Err(SomaError::InvalidManifest(format!(
+                        "name '{}' is reserved for the MySQL database",
+                        MYSQL_SERVICE_NAME
+                    )))?;
+                }
+                Some(mysql.solidify()?)
+            }
+            None => None,
+        };
+
+        // Docker cannot copy the flag into a read-only root file system
+        let flag = match &self.flag {
+            Some(flag) => {
+                if main_service.security().read_only() {
+                    Err(SomaError::InvalidManifest(
+                        "flag cannot be injected into a read-only container".to_owned(),
+                    ))?;
+                }
+                Some(flag.solidify()?)
+            }
+            None => None,
+        };
+
+        // The solver joins the problem network next to the services
+        let solution = match &self.solution {
+            Some(solution) => {
+                if self.name == *SOLVER_SERVICE_NAME
+                    || self.services.keys().any(|name| name == SOLVER_SERVICE_NAME)
+                {
+                    Err(SomaError::InvalidManifest(format!(
+                        "name '{}' is reserved for the solver of the solution",
+                        SOLVER_SERVICE_NAME
+                    )))?;
+                }
+                Some(solution.solidify(flag.is_some())?)
+            }
+            None => None,
+        };
+
+        let services = self
+            .services
+            .iter()
+            .map(|(name, service)| {
+                solidify_service(
+                    &problem_dir,
+                    name,
+                    &service.work_dir,
+                    service.exposed,
+                    &service.env,
+                    &service.limits,
+                    &service.security,
+                    &service.healthcheck,
+                    &service.subconfigs,
+                )
+            })
+            .collect::<SomaResult<_>>()?;
 
         Ok(SolidManifest {
-            name: self.name.clone(),
-            work_dir,
-            binary,
+            main_service,
+            mysql,
+            services,
+            isolated: self.isolated,
+            flag,
+            solution,
         })
     }
 }
 
+impl Subconfigs {
+    fn public_files(&self) -> Vec<&FileSource> {
+        let binary = self.binary.iter().flat_map(BinaryConfig::public_files);
+        let apache_php7 = self
+            .apache_php7
+            .iter()
+            .flat_map(ApachePhp7Config::public_files);
+
+        let python_uwsgi = self
+            .python_uwsgi
+            .iter()
+            .flat_map(PythonUwsgiConfig::public_files);
+
+        let docker = self.docker.iter().flat_map(DockerConfig::public_files);
+
+        binary
+            .chain(apache_php7)
+            .chain(python_uwsgi)
+            .chain(docker)
+            .collect()
+    }
+
+    fn solidify(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+    ) -> SomaResult<SolidConfig> {
+        let mut configs = Vec::new();
+        if let Some(binary) = &self.binary {
+            configs.push(SolidConfig::Binary(
+                binary.solidify(&problem_dir, &work_dir)?,
+            ));
+        }
+        if let Some(apache_php7) = &self.apache_php7 {
+            configs.push(SolidConfig::ApachePhp7(apache_php7.solidify(&problem_dir)?));
+        }
+        if let Some(python_uwsgi) = &self.python_uwsgi {
+            configs.push(SolidConfig::PythonUwsgi(
+                python_uwsgi.solidify(&problem_dir, &work_dir)?,
+            ));
+        }
+        if let Some(docker) = &self.docker {
+            configs.push(SolidConfig::Docker(docker.solidify()?));
+        }
+
+        // A service should contain exactly one subconfiguration
+        if configs.len() != 1 {
+            Err(SomaError::InvalidManifest(format!(
+                "expected exactly one subconfiguration, found {}",
+                configs.len()
+            )))?;
+        }
+        Ok(configs.remove(0))
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn solidify_service(
+    problem_dir: impl AsRef<Path>,
+    name: &NameString,
+    work_dir: &Option<PathBuf>,
+    exposed: bool,
+    env: &BTreeMap<String, String>,
+    limits: &LimitsConfig,
+    security: &SecurityConfig,
+    healthcheck: &Option<HealthcheckConfig>,
+    subconfigs: &Subconfigs,
+) -> SomaResult<SolidService> {
+    let work_dir = match work_dir {
+        Some(path) => path.clone(),
+        None => PathBuf::from(format!("/home/{}", name)),
+    };
+
+    if !work_dir.has_root() {
+        Err(SomaError::InvalidManifest(
+            "work_dir should be an absolute path".to_owned(),
+        ))?;
+    }
+
+    if let Some(key) = env.keys().find(|key| !is_valid_env_key(key)) {
+        Err(SomaError::InvalidManifest(format!(
+            "environment variable name '{}' is invalid",
+            key
+        )))?;
+    }
+
+    let config = subconfigs.solidify(problem_dir, &work_dir)?;
+
+    // Capabilities and seccomp profile of the container are set for nsjail to create namespaces
+    let security = security.solidify()?;
+    if config.creates_namespaces()
+        && (security.capabilities().is_some() || security.seccomp().is_some())
+    {
+        Err(SomaError::InvalidManifest(
+            "capabilities and seccomp are not supported by nsjail daemon".to_owned(),
+        ))?;
+    }
+
+    // Binary problems are probed on their port unless the manifest defines a healthcheck
+    let healthcheck = match (healthcheck, &config) {
+        (Some(_), SolidConfig::Docker(_)) => Err(SomaError::InvalidManifest(
+            "healthcheck of docker subconfiguration should be defined in its Dockerfile".to_owned(),
+        ))?,
+        (Some(healthcheck), _) => Some(healthcheck.solidify()?),
+        (None, SolidConfig::Binary(_)) => Some(SolidHealthcheckConfig::tcp_listen(config.port())),
+        (None, _) => None,
+    };
+
+    Ok(SolidService {
+        name: name.clone(),
+        work_dir,
+        exposed,
+        env: env.clone(),
+        limits: limits.solidify()?,
+        security,
+        healthcheck,
+        config,
+    })
+}
+
+pub fn is_valid_env_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains('=')
+}
+
+impl SolidService {
+    pub fn name(&self) -> &NameString {
+        &self.name
+    }
+
+    pub fn exposed(&self) -> bool {
+        self.exposed
+    }
+
+    pub fn env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    pub fn limits(&self) -> &SolidLimitsConfig {
+        &self.limits
+    }
+
+    pub fn security(&self) -> &SolidSecurityConfig {
+        &self.security
+    }
+
+    pub fn healthcheck(&self) -> Option<&SolidHealthcheckConfig> {
+        self.healthcheck.as_ref()
+    }
+
+    pub fn config(&self) -> &SolidConfig {
+        &self.config
+    }
+}
+
 impl SolidManifest {
-    pub fn binary(&self) -> &SolidBinaryConfig {
-        &self.binary
+    pub fn config(&self) -> &SolidConfig {
+        self.main_service.config()
+    }
+
+    pub fn main_service(&self) -> &SolidService {
+        &self.main_service
+    }
+
+    // Additional services other than the main service
+    pub fn services(&self) -> &Vec<SolidService> {
+        &self.services
+    }
+
+    pub fn mysql(&self) -> Option<&SolidMysqlConfig> {
+        self.mysql.as_ref()
     }
+
+    // Containers cannot reach outside of the problem network
+    pub fn isolated(&self) -> bool {
+        self.isolated
+    }
+
+    // Generated for each run and injected into the main service
+    pub fn flag(&self) -> Option<&SolidFlagConfig> {
+        self.flag.as_ref()
+    }
+
+    pub fn solution(&self) -> Option<&SolidSolutionConfig> {
+        self.solution.as_ref()
+    }
+
+    pub fn service_context<'a>(&'a self, service: &'a SolidService) -> ServiceContext<'a> {
+        ServiceContext {
+            service,
+            mysql: self.mysql(),
+        }
+    }
+}
+
+// Manifests of older schemas are upgraded before the defaults of the current schema apply
+pub fn read_manifest(path: impl AsRef<Path>, defaults: &Table) -> SomaResult<Manifest> {
+    let mut manifest: Value = toml::from_slice(&read_file_contents(path)?)?;
+    schema::migrate(&mut manifest)?;
+    if let Some(manifest) = manifest.as_table_mut() {
+        inherit_defaults(manifest, defaults)?;
+    }
+    Ok(manifest.try_into()?)
+}
+
+// Values in the manifest override defaults, and tables in both are merged recursively
+fn inherit_defaults(manifest: &mut Table, defaults: &Table) -> SomaResult<()> {
+    // Defaults are always written in the current schema
+    if defaults.contains_key(schema::SCHEMA_KEY) {
+        Err(SomaError::InvalidManifest(
+            "defaults should not specify a schema".to_owned(),
+        ))?;
+    }
+
+    if let Some(key) = UNINHERITABLE_KEYS
+        .iter()
+        .find(|key| defaults.contains_key(**key))
+    {
+        Err(SomaError::InvalidManifest(format!(
+            "'{}' cannot be specified in the defaults",
+            key
+        )))?;
+    }
+
+    for (key, default) in defaults {
+        if OPTIONAL_SECTIONS.contains(&key.as_str()) && !manifest.contains_key(key) {
+            continue;
+        }
+        merge_value(manifest.entry(key.clone()), default);
+    }
+    Ok(())
 }
 
-pub fn read_manifest(path: impl AsRef<Path>) -> SomaResult<Manifest> {
-    Ok(toml::from_slice(&read_file_contents(path)?)?)
+fn merge_value(entry: Entry<String, Value>, default: &Value) {
+    match entry {
+        Entry::Vacant(entry) => {
+            entry.insert(default.clone());
+        }
+        Entry::Occupied(mut entry) => {
+            if let (Value::Table(table), Value::Table(default_table)) = (entry.get_mut(), default) {
+                for (key, default) in default_table {
+                    merge_value(table.entry(key.clone()), default);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+
+    use super::*;
+
+    fn local(path: &str) -> FileSource {
+        FileSource::Local {
+            path: PathBuf::from(path),
+        }
+    }
+
+    #[test]
+    fn test_public_files() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "files"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./files"
+
+            [[binary.executable]]
+            path = "files"
+            public = true
+
+            [[binary.readonly]]
+            path = "flag"
+
+            [[binary.readwrite]]
+            path = "notes"
+            public = true
+
+            [[binary.with-permissions]]
+            path = "secret"
+            permissions = "400"
+
+            [[binary.fetchonly]]
+            path = "libc.so.6"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.public_files(),
+            vec![&local("files"), &local("notes"), &local("libc.so.6")]
+        );
+        assert_eq!(
+            manifest.solidify(".").unwrap().config().path_maps().count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_remote_files() {
+        let sha256 = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let manifest: Manifest = toml::from_str(&format!(
+            r#"
+            name = "remote"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./remote"
+
+            [[binary.executable]]
+            path = "remote"
+
+            [[binary.readonly]]
+            url = "https://example.com/files/libc.so.6"
+            sha256 = "{}"
+            public = true
+            "#,
+            sha256
+        ))
+        .unwrap();
+
+        let remote = FileSource::Remote {
+            url: "https://example.com/files/libc.so.6".to_owned(),
+            sha256: sha256.to_owned(),
+        };
+        assert_eq!(manifest.public_files(), vec![&remote]);
+        let manifest = manifest.solidify(".").unwrap();
+        assert_eq!(
+            manifest.config().path_maps().collect::<Vec<_>>(),
+            vec![
+                (&local("remote"), &PathBuf::from("/home/remote/remote")),
+                (&remote, &PathBuf::from("/home/remote/libc.so.6"))
+            ]
+        );
+
+        let solidify = |entry: &str| {
+            let manifest = format!(
+                "name = \"remote\"\n[binary]\nos = \"ubuntu:16.04\"\ncmd = \"./remote\"\n[[binary.readonly]]\n{}",
+                entry
+            );
+            toml::from_str::<Manifest>(&manifest)?.solidify(".")
+        };
+        assert!(solidify(&format!(
+            "url = \"ftp://example.com/libc.so.6\"\nsha256 = \"{}\"",
+            sha256
+        ))
+        .is_err());
+        assert!(solidify(&format!(
+            "url = \"https://example.com/\"\nsha256 = \"{}\"",
+            sha256
+        ))
+        .is_err());
+        assert!(solidify("url = \"https://example.com/libc.so.6\"\nsha256 = \"abcd\"").is_err());
+        assert!(solidify("url = \"https://example.com/libc.so.6\"").is_err());
+    }
+
+    #[test]
+    fn test_list_defaults() {
+        let defaults = read_list_defaults("test_repo/defaults").unwrap();
+
+        let manifest = read_manifest("test_repo/defaults/plain/soma.toml", &defaults)
+            .unwrap()
+            .solidify("test_repo/defaults/plain")
+            .unwrap();
+        let main_service = manifest.main_service();
+        assert_eq!(main_service.work_dir, PathBuf::from("/home/ctf"));
+        assert_eq!(main_service.limits().memory(), Some(256 * 1024 * 1024));
+        assert_eq!(main_service.limits().pids_limit(), Some(64));
+        assert!(Handlebars::new()
+            .render_template("{{{ binary.install_command }}}", &manifest)
+            .unwrap()
+            .starts_with("apt-get"));
+        // Defaults of a section do not enable the section
+        assert!(manifest.flag().is_none());
+
+        let manifest = read_manifest("test_repo/defaults/custom/soma.toml", &defaults)
+            .unwrap()
+            .solidify("test_repo/defaults/custom")
+            .unwrap();
+        let main_service = manifest.main_service();
+        assert_eq!(main_service.work_dir, PathBuf::from("/srv"));
+        assert_eq!(main_service.limits().memory(), Some(1024 * 1024 * 1024));
+        assert_eq!(main_service.limits().pids_limit(), Some(64));
+        assert!(Handlebars::new()
+            .render_template("{{{ binary.install_command }}}", &manifest)
+            .unwrap()
+            .starts_with("apk"));
+        assert!(manifest.flag().unwrap().generate().starts_with("CTF{"));
+
+        let mut manifest = Table::new();
+        let defaults: Table = toml::from_str(r#"name = "shared""#).unwrap();
+        assert!(inherit_defaults(&mut manifest, &defaults).is_err());
+        let defaults: Table = toml::from_str("schema = 1").unwrap();
+        assert!(inherit_defaults(&mut manifest, &defaults).is_err());
+    }
+
+    #[test]
+    fn test_glob_files() {
+        let manifest = read_manifest("test_repo/glob/soma.toml", &Table::new()).unwrap();
+        assert_eq!(manifest.public_files().len(), 0);
+        let manifest = manifest.solidify("test_repo/glob").unwrap();
+        assert_eq!(
+            manifest.config().path_maps().collect::<Vec<_>>(),
+            vec![
+                (&local("glob"), &PathBuf::from("/home/glob/glob")),
+                (
+                    &local("libs/libc.so"),
+                    &PathBuf::from("/usr/lib/glob/libc.so")
+                ),
+                (
+                    &local("libs/libm.so"),
+                    &PathBuf::from("/usr/lib/glob/libm.so")
+                ),
+                (&local("config"), &PathBuf::from("/etc/glob")),
+                (&local("override"), &PathBuf::from("/etc/glob"))
+            ]
+        );
+
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "glob"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./glob"
+
+            [[binary.fetchonly]]
+            path = "libs/*.so"
+
+            [[binary.readonly]]
+            path = "libs/*.so"
+            "#,
+        )
+        .unwrap();
+        let public_file = manifest.public_files()[0];
+        assert!(public_file.is_pattern());
+        assert_eq!(
+            public_file.expand("test_repo/glob").unwrap(),
+            vec![local("libs/libc.so"), local("libs/libm.so")]
+        );
+        let manifest = manifest.solidify("test_repo/glob").unwrap();
+        assert_eq!(
+            manifest
+                .config()
+                .path_maps()
+                .map(|(_, target_path)| target_path)
+                .collect::<Vec<_>>(),
+            vec![
+                &PathBuf::from("/home/glob/libc.so"),
+                &PathBuf::from("/home/glob/libm.so")
+            ]
+        );
+
+        // A pattern without any match is likely a mistake
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "glob"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./glob"
+
+            [[binary.readonly]]
+            path = "libs/*.dll"
+            "#,
+        )
+        .unwrap();
+        assert!(manifest.solidify("test_repo/glob").is_err());
+    }
+
+    #[test]
+    fn test_build() {
+        let manifest = read_manifest("test_repo/build/soma.toml", &Table::new()).unwrap();
+        let manifest = manifest.solidify("test_repo/build").unwrap();
+        assert_eq!(manifest.config().path_maps().count(), 0);
+        assert_eq!(
+            manifest.config().build_path_maps().collect::<Vec<_>>(),
+            vec![
+                (&local("Makefile"), &PathBuf::from("/build/Makefile")),
+                (&local("src/echo.c"), &PathBuf::from("/build/src/echo.c")),
+                (&local("src/main.c"), &PathBuf::from("/build/src/main.c"))
+            ]
+        );
+
+        let dockerfile = Handlebars::new()
+            .render_template(
+                include_str!("../templates/binary/socat/Dockerfile"),
+                &manifest,
+            )
+            .unwrap();
+        assert!(dockerfile.contains("FROM gcc:8 AS builder"));
+        assert!(dockerfile.contains("RUN make"));
+        assert!(
+            dockerfile.contains(r#"COPY --from=builder ["/build/out/build", "/home/build/build"]"#)
+        );
+
+        let configure_permissions = Handlebars::new()
+            .render_template(
+                include_str!("../templates/binary/configure_permissions.sh"),
+                &manifest,
+            )
+            .unwrap();
+        assert!(configure_permissions.contains(r#"chmod 550 "/home/build/build""#));
+
+        let build_manifest = |build: &str| -> SomaResult<SolidManifest> {
+            let manifest: Manifest = toml::from_str(&format!(
+                r#"
+                name = "build"
+
+                [binary]
+                os = "debian:buster"
+                cmd = "./build"
+
+                [binary.build]
+                image = "gcc:8"
+                {}
+                "#,
+                build
+            ))
+            .unwrap();
+            manifest.solidify("test_repo/build")
+        };
+
+        // The whole problem directory is copied when sources are omitted
+        let manifest = build_manifest("command = \"make\"\noutputs = [\"out/build\"]").unwrap();
+        assert_eq!(
+            manifest.config().build_path_maps().collect::<Vec<_>>(),
+            vec![(&local("."), &PathBuf::from("/build/."))]
+        );
+
+        // Paths escaping the problem or build directory are rejected
+        assert!(build_manifest(
+            "sources = [\"../glob\"]\ncommand = \"make\"\noutputs = [\"out/build\"]"
+        )
+        .is_err());
+        assert!(build_manifest("command = \"make\"\noutputs = [\"/bin/sh\"]").is_err());
+        assert!(build_manifest("command = \"make\"\noutputs = []").is_err());
+        assert!(build_manifest("command = \"\"\noutputs = [\"out/build\"]").is_err());
+        assert!(
+            build_manifest("command = \"make\\nrm -rf /\"\noutputs = [\"out/build\"]").is_err()
+        );
+
+        // Images without a build do not render the builder stage
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "plain"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./plain"
+            "#,
+        )
+        .unwrap();
+        let manifest = manifest.solidify(".").unwrap();
+        let dockerfile = Handlebars::new()
+            .render_template(
+                include_str!("../templates/binary/nsjail/Dockerfile"),
+                &manifest,
+            )
+            .unwrap();
+        assert!(!dockerfile.contains("builder"));
+    }
+
+    #[test]
+    fn test_mysql() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "sqli"
+
+            [apache-php7]
+
+            [mysql]
+            init = "init.sql"
+            database = "sqli"
+            user = "sqli"
+            password = "password"
+            "#,
+        )
+        .unwrap();
+        let manifest = manifest.solidify(".").unwrap();
+        let mysql = manifest.mysql().unwrap();
+        assert_eq!(
+            mysql.path_maps().collect::<Vec<_>>(),
+            vec![(
+                &local("init.sql"),
+                &PathBuf::from("/docker-entrypoint-initdb.d/init.sql")
+            )]
+        );
+        let client_env = mysql.client_env();
+        assert_eq!(client_env["MYSQL_HOST"], "mysql");
+        assert_eq!(client_env["MYSQL_PASSWORD"], "password");
+
+        let rendered = Handlebars::new()
+            .render_template("{{ mysql.version }}", &manifest)
+            .unwrap();
+        assert_eq!(rendered, "5.7");
+    }
+
+    #[test]
+    fn test_services() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "web"
+
+            [apache-php7]
+
+            [services.api]
+            exposed = true
+
+            [services.api.python-uwsgi]
+            module = "app:app"
+
+            [[services.api.python-uwsgi.readonly]]
+            path = "app.py"
+            public = true
+
+            [services.worker.binary]
+            os = "ubuntu:16.04"
+            cmd = "./worker"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.public_files(), vec![&local("app.py")]);
+
+        let manifest = manifest.solidify(".").unwrap();
+        assert_eq!(manifest.main_service().name(), "web");
+        let services = manifest.services();
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].name(), "api");
+        assert!(services[0].exposed());
+        assert_eq!(services[1].name(), "worker");
+        assert!(!services[1].exposed());
+
+        let rendered = Handlebars::new()
+            .render_template(
+                "{{ name }};{{ work_dir }};{{ python_uwsgi.module }}",
+                &manifest.service_context(&services[0]),
+            )
+            .unwrap();
+        assert_eq!(rendered, "api;/home/api;app:app");
+    }
+
+    #[test]
+    fn test_service_names() {
+        let main_name: Manifest = toml::from_str(
+            r#"
+            name = "web"
+
+            [apache-php7]
+
+            [services.web.apache-php7]
+            "#,
+        )
+        .unwrap();
+        assert!(main_name.solidify(".").is_err());
+
+        let mysql_name: Manifest = toml::from_str(
+            r#"
+            name = "web"
+
+            [apache-php7]
+
+            [services.mysql.apache-php7]
+
+            [mysql]
+            database = "web"
+            user = "web"
+            password = "password"
+            "#,
+        )
+        .unwrap();
+        assert!(mysql_name.solidify(".").is_err());
+
+        let no_config: Manifest = toml::from_str(
+            r#"
+            name = "web"
+
+            [apache-php7]
+
+            [services.api]
+            exposed = true
+            "#,
+        )
+        .unwrap();
+        assert!(no_config.solidify(".").is_err());
+
+        // The solver container joins the problem network with a reserved host name
+        let reserved: Manifest = toml::from_str(
+            r#"
+            name = "solver"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./solver"
+
+            [solution]
+            image = "python:3"
+            script = "solve.py"
+            flag = "flag{reserved}"
+            "#,
+        )
+        .unwrap();
+        assert!(reserved.solidify(".").is_err());
+    }
+
+    #[test]
+    fn test_docker() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "custom"
+
+            [docker]
+            port = 8080
+            "#,
+        )
+        .unwrap();
+        let manifest = manifest.solidify(".").unwrap();
+        assert_eq!(manifest.config().port(), 8080);
+        assert_eq!(manifest.config().path_maps().count(), 0);
+        match manifest.config() {
+            SolidConfig::Docker(config) => {
+                assert_eq!(config.dockerfile(), "Dockerfile");
+                assert_eq!(config.context(), &PathBuf::from("."));
+            }
+            _ => panic!("Unexpected subconfiguration"),
+        }
+
+        let absolute_context: Manifest = toml::from_str(
+            r#"
+            name = "custom"
+
+            [docker]
+            context = "/etc"
+            port = 8080
+            "#,
+        )
+        .unwrap();
+        assert!(absolute_context.solidify(".").is_err());
+
+        let parent_context: Manifest = toml::from_str(
+            r#"
+            name = "custom"
+
+            [docker]
+            context = ".."
+            port = 8080
+            "#,
+        )
+        .unwrap();
+        assert!(parent_context.solidify(".").is_err());
+
+        let parent_dockerfile: Manifest = toml::from_str(
+            r#"
+            name = "custom"
+
+            [docker]
+            dockerfile = "../Dockerfile"
+            port = 8080
+            "#,
+        )
+        .unwrap();
+        assert!(parent_dockerfile.solidify(".").is_err());
+    }
+
+    #[test]
+    fn test_env() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "env"
+
+            [apache-php7]
+
+            [env]
+            FLAG_PATH = "/flag"
+
+            [services.api.python-uwsgi]
+            module = "app:app"
+
+            [services.api.env]
+            DIFFICULTY = "hard"
+            "#,
+        )
+        .unwrap();
+        let manifest = manifest.solidify(".").unwrap();
+        assert_eq!(manifest.main_service().env()["FLAG_PATH"], "/flag");
+        assert_eq!(manifest.services()[0].env()["DIFFICULTY"], "hard");
+
+        let invalid_key: Manifest = toml::from_str(
+            r#"
+            name = "env"
+
+            [apache-php7]
+
+            [env]
+            "A=B" = "C"
+            "#,
+        )
+        .unwrap();
+        assert!(invalid_key.solidify(".").is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "limits"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./limits"
+
+            [limits]
+            memory = "256m"
+            cpu_shares = 512
+            pids_limit = 64
+            nproc = 32
+            fsize = "1m"
+            tmpfs = "16m"
+
+            [services.api.python-uwsgi]
+            module = "app:app"
+            "#,
+        )
+        .unwrap();
+        let manifest = manifest.solidify(".").unwrap();
+
+        let limits = manifest.main_service().limits();
+        assert_eq!(limits.memory(), Some(256 * 1024 * 1024));
+        assert_eq!(limits.cpu_shares(), Some(512));
+        assert_eq!(limits.pids_limit(), Some(64));
+        assert_eq!(limits.nproc(), Some(32));
+        assert_eq!(limits.fsize(), Some(1024 * 1024));
+        assert_eq!(limits.tmpfs(), Some(16 * 1024 * 1024));
+        assert_eq!(
+            manifest.services()[0].limits(),
+            &SolidLimitsConfig::default()
+        );
+
+        let invalid_size: Manifest = toml::from_str(
+            r#"
+            name = "limits"
+
+            [apache-php7]
+
+            [limits]
+            memory = "lots"
+            "#,
+        )
+        .unwrap();
+        assert!(invalid_size.solidify(".").is_err());
+    }
+
+    #[test]
+    fn test_security() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "security"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./security"
+
+            [security]
+            capabilities = ["chown", "CAP_SETUID", "SETGID"]
+            seccomp = "seccomp.json"
+            no_new_privileges = true
+            read_only = true
+
+            [services.api.python-uwsgi]
+            module = "app:app"
+
+            [services.api.security]
+            tmpfs = ["/var/run"]
+            "#,
+        )
+        .unwrap();
+        let manifest = manifest.solidify(".").unwrap();
+
+        let security = manifest.main_service().security();
+        assert_eq!(
+            security.capabilities().unwrap(),
+            &vec!["CHOWN", "CAP_SETUID", "SETGID"]
+        );
+        assert_eq!(security.seccomp(), Some(&PathBuf::from("seccomp.json")));
+        assert!(security.no_new_privileges());
+        assert!(security.read_only());
+        assert_eq!(security.tmpfs(), &vec!["/tmp"]);
+
+        let security = manifest.services()[0].security();
+        assert_eq!(security.capabilities(), None);
+        assert!(!security.read_only());
+        assert_eq!(security.tmpfs(), &vec!["/var/run"]);
+
+        let security = |security: &str| -> SomaResult<SolidManifest> {
+            let manifest: Manifest = toml::from_str(&format!(
+                r#"
+                name = "security"
+
+                [binary]
+                os = "ubuntu:16.04"
+                cmd = "./security"
+                daemon = "nsjail"
+
+                [security]
+                {}
+                "#,
+                security
+            ))
+            .unwrap();
+            manifest.solidify(".")
+        };
+        assert!(security("").is_ok());
+        assert!(security("read_only = true").is_ok());
+        assert!(security("no_new_privileges = true").is_ok());
+        assert!(security(r#"capabilities = ["SETUID"]"#).is_err());
+        assert!(security(r#"seccomp = "seccomp.json""#).is_err());
+    }
+
+    #[test]
+    fn test_default_healthcheck() {
+        let manifest = |manifest: &str| -> SomaResult<SolidManifest> {
+            toml::from_str::<Manifest>(manifest).unwrap().solidify(".")
+        };
+
+        // Binary problems check the listening socket of the daemon by default
+        let binary = manifest(
+            r#"
+            name = "binary"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./binary"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            binary.main_service().healthcheck(),
+            Some(&SolidHealthcheckConfig::tcp_listen(1337))
+        );
+        let dockerfile = Handlebars::new()
+            .render_template(
+                include_str!("../templates/binary/xinetd/Dockerfile"),
+                &binary,
+            )
+            .unwrap();
+        assert!(dockerfile.contains(
+            "HEALTHCHECK --interval=5s --timeout=5s --start-period=0s --retries=3 \\\n    \
+             CMD cat /proc/net/tcp"
+        ));
+
+        // Other subconfigurations are not probed without a healthcheck
+        let flask = manifest(
+            r#"
+            name = "flask"
+
+            [python-uwsgi]
+            module = "app:app"
+            "#,
+        )
+        .unwrap();
+        assert!(flask.main_service().healthcheck().is_none());
+        let dockerfile = Handlebars::new()
+            .render_template(include_str!("../templates/python-uwsgi/Dockerfile"), &flask)
+            .unwrap();
+        assert!(!dockerfile.contains("HEALTHCHECK"));
+
+        let services = manifest(
+            r#"
+            name = "main"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./main"
+
+            [services.api.python-uwsgi]
+            module = "app:app"
+
+            [services.api.healthcheck]
+            cmd = "true"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(services.services()[0].healthcheck().unwrap().cmd(), "true");
+
+        // Custom Dockerfiles define their own HEALTHCHECK
+        assert!(manifest(
+            r#"
+            name = "custom"
+
+            [docker]
+            port = 8080
+
+            [healthcheck]
+            cmd = "true"
+            "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_isolated() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "isolated"
+            isolated = true
+
+            [apache-php7]
+            "#,
+        )
+        .unwrap();
+        assert!(manifest.solidify(".").unwrap().isolated());
+
+        let manifest: Manifest = toml::from_str(
+            r#"
+            name = "connected"
+
+            [apache-php7]
+            "#,
+        )
+        .unwrap();
+        assert!(!manifest.solidify(".").unwrap().isolated());
+    }
+
+    #[test]
+    fn test_subconfig_count() {
+        let no_config: Manifest = toml::from_str(r#"name = "none""#).unwrap();
+        assert!(no_config.solidify(".").is_err());
+
+        let two_configs: Manifest = toml::from_str(
+            r#"
+            name = "two"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./two"
+
+            [apache-php7]
+            "#,
+        )
+        .unwrap();
+        assert!(two_configs.solidify(".").is_err());
+    }
 }
@@ -1,5 +1,103 @@
-pub use self::binary::{BinaryConfig, SolidBinaryConfig};
-pub use self::common::{FileEntry, SolidFileEntry};
+use std::path::PathBuf;
 
+use serde::Serialize;
+
+pub use self::apache_php7::{ApachePhp7Config, SolidApachePhp7Config};
+pub use self::binary::{BinaryConfig, Daemon, SolidBinaryConfig};
+pub use self::build::{BuildConfig, SolidBuildConfig, SolidBuildOutput};
+pub use self::common::{FileEntry, FileEntryWithPermissions, FileSource, SolidFileEntry};
+pub use self::docker::{DockerConfig, SolidDockerConfig};
+pub use self::flag::{FlagConfig, SolidFlagConfig};
+pub use self::healthcheck::{HealthcheckConfig, SolidHealthcheckConfig};
+pub use self::limits::{LimitsConfig, SolidLimitsConfig};
+pub use self::mysql::{MysqlConfig, SolidMysqlConfig, MYSQL_SERVICE_NAME};
+pub use self::python_uwsgi::{PythonUwsgiConfig, SolidPythonUwsgiConfig};
+pub use self::security::{SecurityConfig, SolidSecurityConfig};
+pub use self::solution::{SolidSolutionConfig, SolutionConfig, SOLVER_SERVICE_NAME};
+use crate::template::Templates;
+
+mod apache_php7;
 mod binary;
+mod build;
 mod common;
+mod docker;
+mod flag;
+mod healthcheck;
+mod limits;
+mod mysql;
+mod python_uwsgi;
+mod security;
+mod solution;
+
+// Serialized with the subconfiguration name as a key to be used in templates
+#[derive(Serialize)]
+pub enum SolidConfig {
+    #[serde(rename = "binary")]
+    Binary(SolidBinaryConfig),
+    #[serde(rename = "apache_php7")]
+    ApachePhp7(SolidApachePhp7Config),
+    #[serde(rename = "python_uwsgi")]
+    PythonUwsgi(SolidPythonUwsgiConfig),
+    #[serde(rename = "docker")]
+    Docker(SolidDockerConfig),
+}
+
+impl SolidConfig {
+    // Custom Dockerfiles are built as is instead of being rendered from templates
+    pub fn templates(&self) -> Option<Templates> {
+        match self {
+            SolidConfig::Binary(config) => Some(match config.daemon() {
+                Daemon::Socat => Templates::BinarySocat,
+                Daemon::Xinetd => Templates::BinaryXinetd,
+                Daemon::Nsjail => Templates::BinaryNsjail,
+            }),
+            SolidConfig::ApachePhp7(_) => Some(Templates::ApachePhp7),
+            SolidConfig::PythonUwsgi(_) => Some(Templates::PythonUwsgi),
+            SolidConfig::Docker(_) => None,
+        }
+    }
+
+    // Nsjail creates namespaces for each connection, which docker forbids by default
+    pub fn creates_namespaces(&self) -> bool {
+        match self {
+            SolidConfig::Binary(config) => config.daemon() == Daemon::Nsjail,
+            _ => false,
+        }
+    }
+
+    // Nsjail runs the problem in a root file system copied into the container
+    pub fn root_dir(&self) -> &'static str {
+        match self {
+            SolidConfig::Binary(config) if config.daemon() == Daemon::Nsjail => "/chroot",
+            _ => "/",
+        }
+    }
+
+    pub fn port(&self) -> u16 {
+        match self {
+            SolidConfig::Binary(config) => config.port(),
+            SolidConfig::ApachePhp7(config) => config.port(),
+            SolidConfig::PythonUwsgi(config) => config.port(),
+            SolidConfig::Docker(config) => config.port(),
+        }
+    }
+
+    pub fn path_maps(&self) -> impl Iterator<Item = (&FileSource, &PathBuf)> + '_ {
+        let file_entries = match self {
+            SolidConfig::Binary(config) => config.file_entries(),
+            SolidConfig::ApachePhp7(config) => config.file_entries(),
+            SolidConfig::PythonUwsgi(config) => config.file_entries(),
+            SolidConfig::Docker(config) => config.file_entries(),
+        };
+        file_entries.iter().map(SolidFileEntry::path_map)
+    }
+
+    // Sources copied into the builder stage of a multi-stage build
+    pub fn build_path_maps(&self) -> impl Iterator<Item = (&FileSource, &PathBuf)> + '_ {
+        let build = match self {
+            SolidConfig::Binary(config) => config.build(),
+            _ => None,
+        };
+        build.into_iter().flat_map(SolidBuildConfig::path_maps)
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::common::{serialize_as_slash_path, FileEntries, FileSource, SolidFileEntry};
+use crate::prelude::*;
+
+const DEFAULT_DOCUMENT_ROOT: &str = "/var/www/html";
+const DEFAULT_PORT: u16 = 80;
+
+#[derive(Deserialize)]
+pub struct ApachePhp7Config {
+    document_root: Option<PathBuf>,
+    port: Option<u16>,
+    #[serde(default)]
+    php_ini: BTreeMap<String, toml::Value>,
+    #[serde(flatten)]
+    file_entries: FileEntries,
+}
+
+#[derive(Serialize)]
+pub struct SolidApachePhp7Config {
+    #[serde(serialize_with = "serialize_as_slash_path")]
+    document_root: PathBuf,
+    port: u16,
+    php_ini: BTreeMap<String, toml::Value>,
+    file_entries: Vec<SolidFileEntry>,
+}
+
+impl ApachePhp7Config {
+    pub fn public_files(&self) -> Vec<&FileSource> {
+        self.file_entries.public_files()
+    }
+
+    // File entries default to the document root instead of the working directory
+    pub fn solidify(&self, problem_dir: impl AsRef<Path>) -> SomaResult<SolidApachePhp7Config> {
+        let document_root = match &self.document_root {
+            Some(path) => path.clone(),
+            None => PathBuf::from(DEFAULT_DOCUMENT_ROOT),
+        };
+
+        if !document_root.has_root() {
+            Err(SomaError::InvalidManifest(
+                "document_root should be an absolute path".to_owned(),
+            ))?;
+        }
+
+        let file_entries = self.file_entries.solidify(problem_dir, &document_root)?;
+
+        Ok(SolidApachePhp7Config {
+            document_root,
+            port: self.port.unwrap_or(DEFAULT_PORT),
+            php_ini: self.php_ini.clone(),
+            file_entries,
+        })
+    }
+}
+
+impl SolidApachePhp7Config {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn file_entries(&self) -> &Vec<SolidFileEntry> {
+        &self.file_entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+
+    use super::*;
+
+    #[test]
+    fn test_apache_php7() {
+        let config: ApachePhp7Config = toml::from_str(
+            r#"
+            port = 8080
+
+            [php_ini]
+            display_errors = "Off"
+            max_execution_time = 30
+
+            [[readonly]]
+            path = "www/index.php"
+            "#,
+        )
+        .unwrap();
+        let config = config.solidify(".").unwrap();
+        assert_eq!(config.port(), 8080);
+
+        let rendered = Handlebars::new()
+            .render_template(
+                "{{ #each file_entries }}{{ target_path }}{{ /each }}\
+                 {{ #each php_ini }};{{ @key }}={{ this }}{{ /each }}",
+                &config,
+            )
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "/var/www/html/index.php;display_errors=Off;max_execution_time=30"
+        );
+
+        let relative_root: ApachePhp7Config = toml::from_str(r#"document_root = "www""#).unwrap();
+        assert!(relative_root.solidify(".").is_err());
+    }
+}
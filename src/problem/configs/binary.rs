@@ -1,55 +1,393 @@
-use std::path::{Path, PathBuf};
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
 
 use serde::{Deserialize, Serialize};
 
-use super::common::{FileEntry, FilePermissions, SolidFileEntry};
+use super::build::{BuildConfig, SolidBuildConfig};
+use super::common::{FileEntries, FileSource, SolidFileEntry};
 use crate::prelude::*;
 
+const DEFAULT_PORT: u16 = 1337;
+
+lazy_static! {
+    // Allows version specifiers such as "python3=3.5.1-3" or "python3-3.6.8"
+    static ref PACKAGE_REGEX: Regex = Regex::new(r"^[A-Za-z0-9][A-Za-z0-9.+_:=~-]*$").unwrap();
+}
+
+// Parent images supported by the binary template and their package managers
+const SUPPORTED_OS: &[(&str, PackageManager)] = &[
+    ("ubuntu", PackageManager::Apt),
+    ("debian", PackageManager::Apt),
+    ("alpine", PackageManager::Apk),
+    ("centos", PackageManager::Yum),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PackageManager {
+    Apt,
+    Apk,
+    Yum,
+}
+
+impl PackageManager {
+    // Alpine images need shadow package for useradd
+    fn install_command(self, packages: &[&str]) -> String {
+        let packages = packages.join(" ");
+        match self {
+            PackageManager::Apt => format!(
+                "apt-get -qq update && apt-get -yqq upgrade && apt-get install -yqq {}",
+                packages
+            ),
+            PackageManager::Apk => format!(
+                "apk upgrade --no-cache && apk add --no-cache shadow {}",
+                packages
+            ),
+            // Yum fails when no package is given to install
+            PackageManager::Yum if packages.is_empty() => {
+                "yum -q -y update && yum clean all".to_owned()
+            }
+            PackageManager::Yum => format!(
+                "yum -q -y update && yum -q -y install {} && yum clean all",
+                packages
+            ),
+        }
+    }
+}
+
+// Fork daemon that accepts connections and runs the problem binary for each of them
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Daemon {
+    #[default]
+    Socat,
+    Xinetd,
+    Nsjail,
+}
+
+impl Daemon {
+    // Nsjail is built in the image instead of being installed from the distribution
+    fn package(self) -> Option<&'static str> {
+        match self {
+            Daemon::Socat => Some("socat"),
+            Daemon::Xinetd => Some("xinetd"),
+            Daemon::Nsjail => None,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct BinaryConfig {
     os: String,
     cmd: String,
-    executable: Vec<FileEntry>,
-    readonly: Vec<FileEntry>,
+    #[serde(default)]
+    daemon: Daemon,
+    port: Option<u16>,
+    // Limits of each connection in seconds
+    timeout: Option<u64>,
+    idle_timeout: Option<u64>,
+    #[serde(default)]
+    packages: Vec<String>,
+    // Compiles executables from source in a builder stage
+    build: Option<BuildConfig>,
+    #[serde(flatten)]
+    file_entries: FileEntries,
 }
 
 #[derive(Serialize)]
 pub struct SolidBinaryConfig {
     os: String,
+    install_command: String,
     cmd: String,
+    #[serde(skip)]
+    daemon: Daemon,
+    port: u16,
+    timeout: Option<u64>,
+    idle_timeout: Option<u64>,
+    build: Option<SolidBuildConfig>,
     file_entries: Vec<SolidFileEntry>,
 }
 
 impl BinaryConfig {
-    pub fn executable(&self) -> &Vec<FileEntry> {
-        &self.executable
+    pub fn public_files(&self) -> Vec<&FileSource> {
+        self.file_entries.public_files()
     }
 
-    pub fn readonly(&self) -> &Vec<FileEntry> {
-        &self.readonly
-    }
+    pub fn solidify(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+    ) -> SomaResult<SolidBinaryConfig> {
+        let package_manager = package_manager(&self.os)?;
 
-    pub fn solidify(&self, work_dir: impl AsRef<Path>) -> SomaResult<SolidBinaryConfig> {
-        let executable = self
-            .executable
+        // Package names are rendered into a shell command
+        if let Some(package) = self
+            .packages
             .iter()
-            .map(|file| file.solidify(&work_dir, FilePermissions::Executable));
-        let readonly = self
-            .readonly
-            .iter()
-            .map(|file| file.solidify(&work_dir, FilePermissions::ReadOnly));
-        let file_entries = executable.chain(readonly).collect::<SomaResult<Vec<_>>>()?;
+            .find(|package| !PACKAGE_REGEX.is_match(package))
+        {
+            Err(SomaError::InvalidManifest(format!(
+                "package name '{}' contains unsupported characters",
+                package
+            )))?;
+        }
+
+        // Zero disables the timeout of the timeout command, so it is rejected
+        if self.timeout == Some(0) || self.idle_timeout == Some(0) {
+            Err(SomaError::InvalidManifest(
+                "timeout and idle_timeout should be positive".to_owned(),
+            ))?;
+        }
+
+        // Only socat can close a connection without any data transferred
+        if self.idle_timeout.is_some() && self.daemon != Daemon::Socat {
+            Err(SomaError::InvalidManifest(
+                "idle_timeout is only supported by socat daemon".to_owned(),
+            ))?;
+        }
+
+        // Extra packages are installed in the same layer as the daemon
+        let packages: Vec<&str> = self
+            .daemon
+            .package()
+            .into_iter()
+            .chain(self.packages.iter().map(String::as_str))
+            .collect();
+
+        let build = match &self.build {
+            Some(build) => Some(build.solidify(&problem_dir, &work_dir)?),
+            None => None,
+        };
 
         Ok(SolidBinaryConfig {
             os: self.os.clone(),
+            install_command: package_manager.install_command(&packages),
             cmd: self.cmd.clone(),
-            file_entries,
+            daemon: self.daemon,
+            port: self.port.unwrap_or(DEFAULT_PORT),
+            timeout: self.timeout,
+            idle_timeout: self.idle_timeout,
+            build,
+            file_entries: self.file_entries.solidify(problem_dir, work_dir)?,
         })
     }
 }
 
 impl SolidBinaryConfig {
-    pub fn path_maps(&self) -> impl Iterator<Item = (&PathBuf, &PathBuf)> + '_ {
-        self.file_entries.iter().map(SolidFileEntry::path_map)
+    pub fn daemon(&self) -> Daemon {
+        self.daemon
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn build(&self) -> Option<&SolidBuildConfig> {
+        self.build.as_ref()
+    }
+
+    pub fn file_entries(&self) -> &Vec<SolidFileEntry> {
+        &self.file_entries
+    }
+}
+
+// The tag of the image is not restricted, e.g. "ubuntu:16.04" resolves to "ubuntu"
+fn package_manager(os: &str) -> SomaResult<PackageManager> {
+    let image_name = os.split(':').next().unwrap_or(os);
+    match SUPPORTED_OS.iter().find(|(name, _)| *name == image_name) {
+        Some((_, package_manager)) => Ok(*package_manager),
+        None => {
+            let supported_os: Vec<&str> = SUPPORTED_OS.iter().map(|(name, _)| *name).collect();
+            Err(SomaError::InvalidManifest(format!(
+                "os '{}' is not supported, expected one of {} with an optional tag",
+                os,
+                supported_os.join(", ")
+            )))?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+
+    use super::*;
+    use crate::problem::configs::SolidConfig;
+
+    // Fields of the solid manifest which the binary templates refer to
+    #[derive(Serialize)]
+    struct Context<'a> {
+        name: &'a str,
+        work_dir: String,
+        binary: &'a SolidBinaryConfig,
+    }
+
+    fn render(template: &str, name: &str, binary: &SolidBinaryConfig) -> String {
+        let context = Context {
+            name,
+            work_dir: format!("/home/{}", name),
+            binary,
+        };
+        Handlebars::new()
+            .render_template(template, &context)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_binary_os() {
+        let install_command = |os: &str| -> SomaResult<String> {
+            let config: BinaryConfig = toml::from_str(&format!(
+                r#"
+                os = "{}"
+                cmd = "./os"
+                "#,
+                os
+            ))
+            .unwrap();
+            Ok(config.solidify(".", "/home/os")?.install_command)
+        };
+
+        let ubuntu = install_command("ubuntu:16.04").unwrap();
+        assert!(ubuntu.contains("apt-get install -yqq socat"));
+        assert!(!ubuntu.contains("apk"));
+        assert!(install_command("alpine")
+            .unwrap()
+            .contains("apk add --no-cache shadow socat"));
+        assert!(install_command("centos:7")
+            .unwrap()
+            .contains("yum -q -y install socat"));
+
+        let error = install_command("archlinux").err().unwrap();
+        assert!(error
+            .to_string()
+            .contains("os 'archlinux' is not supported"));
+    }
+
+    #[test]
+    fn test_binary_packages() {
+        let config: BinaryConfig = toml::from_str(
+            r#"
+            os = "ubuntu:18.04"
+            cmd = "python3 ./packages.py"
+            packages = ["libseccomp2", "python3=3.6.7-1~18.04"]
+            "#,
+        )
+        .unwrap();
+        assert!(config
+            .solidify(".", "/home/packages")
+            .unwrap()
+            .install_command
+            .ends_with("apt-get install -yqq socat libseccomp2 python3=3.6.7-1~18.04"));
+
+        let injection: BinaryConfig = toml::from_str(
+            r#"
+            os = "ubuntu:18.04"
+            cmd = "./packages"
+            packages = ["socat; rm -rf /"]
+            "#,
+        )
+        .unwrap();
+        assert!(injection.solidify(".", "/home/packages").is_err());
+    }
+
+    #[test]
+    fn test_binary_timeout() {
+        let start_script = |binary: &str| -> SomaResult<String> {
+            let config: BinaryConfig = toml::from_str(&format!(
+                r#"
+                os = "ubuntu:16.04"
+                cmd = "./timeout"
+                {}
+                "#,
+                binary
+            ))
+            .unwrap();
+            Ok(render(
+                include_str!("../../../templates/binary/socat/start.sh"),
+                "timeout",
+                &config.solidify(".", "/home/timeout")?,
+            ))
+        };
+
+        let no_timeout = start_script("").unwrap();
+        assert!(no_timeout.contains("socat tcp-listen:1337"));
+        assert!(no_timeout.contains(r#"exec:"./timeout""#));
+
+        let timeout = start_script("timeout = 60\nidle_timeout = 10").unwrap();
+        assert!(timeout.contains("socat -T 10 tcp-listen:1337"));
+        assert!(timeout.contains(r#"exec:"timeout -s KILL 60 ./timeout""#));
+
+        assert!(start_script("timeout = 0").is_err());
+    }
+
+    #[test]
+    fn test_binary_daemon() {
+        let solidify = |os: &str, daemon: &str| -> SomaResult<SolidBinaryConfig> {
+            let config: BinaryConfig = toml::from_str(&format!(
+                r#"
+                os = "{}"
+                cmd = "./daemon"
+                daemon = "{}"
+                timeout = 30
+                "#,
+                os, daemon
+            ))
+            .unwrap();
+            config.solidify(".", "/home/daemon")
+        };
+
+        let socat = solidify("ubuntu:16.04", "socat").unwrap();
+        assert!(!SolidConfig::Binary(socat).creates_namespaces());
+
+        let xinetd = solidify("ubuntu:16.04", "xinetd").unwrap();
+        assert!(xinetd
+            .install_command
+            .ends_with("apt-get install -yqq xinetd"));
+        assert!(render(
+            include_str!("../../../templates/binary/xinetd/xinetd.conf"),
+            "daemon",
+            &xinetd
+        )
+        .contains("port        = 1337"));
+        assert!(render(
+            include_str!("../../../templates/binary/xinetd/run.sh"),
+            "daemon",
+            &xinetd
+        )
+        .contains("exec timeout -s KILL 30 ./daemon"));
+        assert!(!SolidConfig::Binary(xinetd).creates_namespaces());
+
+        let nsjail = solidify("centos:7", "nsjail").unwrap();
+        assert_eq!(nsjail.install_command, "yum -q -y update && yum clean all");
+        let start_script = render(
+            include_str!("../../../templates/binary/nsjail/start.sh"),
+            "daemon",
+            &nsjail,
+        );
+        assert!(start_script.contains("--port 1337"));
+        assert!(start_script.contains("--cwd \"/home/daemon\""));
+        assert!(start_script.contains("--time_limit 30"));
+        assert!(SolidConfig::Binary(nsjail).creates_namespaces());
+
+        assert!(toml::from_str::<BinaryConfig>(
+            r#"
+            os = "ubuntu:16.04"
+            cmd = "./daemon"
+            daemon = "inetd"
+            "#,
+        )
+        .is_err());
+
+        let idle_timeout: BinaryConfig = toml::from_str(
+            r#"
+            os = "ubuntu:16.04"
+            cmd = "./daemon"
+            daemon = "nsjail"
+            idle_timeout = 10
+            "#,
+        )
+        .unwrap();
+        assert!(idle_timeout.solidify(".", "/home/daemon").is_err());
     }
 }
@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use path_slash::PathBufExt;
+use serde::{Deserialize, Serialize};
+
+use super::common::{validate_relative_path, FileSource};
+use crate::prelude::*;
+
+// Sources are copied into this directory of the builder stage
+const BUILD_DIR: &str = "/build";
+
+#[derive(Deserialize)]
+pub struct BuildConfig {
+    image: String,
+    #[serde(default = "default_sources")]
+    sources: Vec<PathBuf>,
+    command: String,
+    outputs: Vec<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct SolidBuildConfig {
+    image: String,
+    work_dir: String,
+    command: String,
+    // Source files and their paths in the builder stage
+    #[serde(skip)]
+    sources: Vec<(FileSource, PathBuf)>,
+    outputs: Vec<SolidBuildOutput>,
+}
+
+// Built file copied from the builder stage into the final stage
+#[derive(Serialize)]
+pub struct SolidBuildOutput {
+    path: String,
+    target_path: String,
+}
+
+fn default_sources() -> Vec<PathBuf> {
+    vec![PathBuf::from(".")]
+}
+
+impl BuildConfig {
+    pub fn solidify(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+    ) -> SomaResult<SolidBuildConfig> {
+        // Both are rendered into single lines of the Dockerfile
+        let is_single_line = |text: &str| !text.trim().is_empty() && !text.contains(['\n', '\r']);
+        if !is_single_line(&self.image) || !is_single_line(&self.command) {
+            Err(SomaError::InvalidManifest(
+                "build image and command should be non-empty single lines".to_owned(),
+            ))?;
+        }
+
+        if self.outputs.is_empty() {
+            Err(SomaError::InvalidManifest(
+                "build should have at least one output".to_owned(),
+            ))?;
+        }
+
+        let mut sources = Vec::new();
+        for path in &self.sources {
+            validate_relative_path(path, "build source")?;
+            let source = FileSource::Local { path: path.clone() };
+            // Local patterns only expand to local paths
+            for source in source.expand(&problem_dir)? {
+                if let FileSource::Local { path } = &source {
+                    let target_path = Path::new(BUILD_DIR).join(path);
+                    sources.push((source, target_path));
+                }
+            }
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|path| {
+                validate_relative_path(path, "build output")?;
+                let file_name = path.file_name().ok_or(SomaError::FileNameNotFound)?;
+                Ok(SolidBuildOutput {
+                    path: Path::new(BUILD_DIR)
+                        .join(path)
+                        .to_slash()
+                        .ok_or(SomaError::InvalidUnicode)?,
+                    target_path: work_dir
+                        .as_ref()
+                        .join(file_name)
+                        .to_slash()
+                        .ok_or(SomaError::InvalidUnicode)?,
+                })
+            })
+            .collect::<SomaResult<Vec<_>>>()?;
+
+        Ok(SolidBuildConfig {
+            image: self.image.clone(),
+            work_dir: BUILD_DIR.to_owned(),
+            command: self.command.clone(),
+            sources,
+            outputs,
+        })
+    }
+}
+
+impl SolidBuildConfig {
+    pub fn path_maps(&self) -> impl Iterator<Item = (&FileSource, &PathBuf)> {
+        self.sources
+            .iter()
+            .map(|(source, target_path)| (source, target_path))
+    }
+
+    pub fn outputs(&self) -> &Vec<SolidBuildOutput> {
+        &self.outputs
+    }
+}
+
+impl SolidBuildOutput {
+    pub fn target_path(&self) -> &str {
+        &self.target_path
+    }
+}
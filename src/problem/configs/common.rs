@@ -1,19 +1,23 @@
+use std::ffi::OsString;
 use std::fmt;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-use path_slash::PathBufExt;
+use glob::Pattern;
+use path_slash::{PathBufExt, PathExt};
 use serde::de::{self, Deserializer, Unexpected, Visitor};
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
+use url::Url;
 
+use crate::cache::{is_valid_sha256, SHA256_HEX_LENGTH};
 use crate::prelude::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FilePermissions {
     Custom(u16),
     Executable,
     ReadOnly,
-    // Reserved: FetchOnly, ReadWrite
+    ReadWrite,
 }
 
 impl Serialize for FilePermissions {
@@ -25,6 +29,7 @@ impl Serialize for FilePermissions {
             FilePermissions::Custom(permissions) => format!("{:o}", permissions),
             FilePermissions::Executable => "550".to_owned(),
             FilePermissions::ReadOnly => "440".to_owned(),
+            FilePermissions::ReadWrite => "660".to_owned(),
         };
         serializer.serialize_str(&permissions_string)
     }
@@ -64,7 +69,7 @@ impl<'de> Visitor<'de> for FilePermissionsVisitor {
     }
 }
 
-fn serialize_as_slash_path<S>(path_buf: &PathBuf, serializer: S) -> Result<S::Ok, S::Error>
+pub fn serialize_as_slash_path<S>(path_buf: &PathBuf, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -76,26 +81,168 @@ where
     }
 }
 
+// Paths resolved inside the problem directory should not escape it
+pub fn validate_relative_path(path: &Path, kind: &str) -> SomaResult<()> {
+    let is_relative = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_relative {
+        Err(SomaError::InvalidManifest(format!(
+            "{} '{}' should be a relative path without '..'",
+            kind,
+            path.display()
+        )))?;
+    }
+    Ok(())
+}
+
+// Either a path relative to the problem directory or a remote file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum FileSource {
+    Local { path: PathBuf },
+    // Downloaded into the cache and verified before use
+    Remote { url: String, sha256: String },
+}
+
 // target_path is defined as String instead of PathBuf to support Windows
 #[derive(Deserialize)]
 pub struct FileEntry {
-    path: PathBuf,
+    #[serde(flatten)]
+    source: FileSource,
     public: Option<bool>,
     target_path: Option<PathBuf>,
 }
 
+#[derive(Deserialize)]
+pub struct FileEntryWithPermissions {
+    #[serde(flatten)]
+    file_entry: FileEntry,
+    permissions: FilePermissions,
+}
+
+#[derive(Deserialize)]
+pub struct FileEntries {
+    #[serde(default)]
+    executable: Vec<FileEntry>,
+    #[serde(default)]
+    readonly: Vec<FileEntry>,
+    #[serde(default)]
+    readwrite: Vec<FileEntry>,
+    #[serde(default, rename = "with-permissions")]
+    with_permissions: Vec<FileEntryWithPermissions>,
+    // Fetch-only entries are provided to users but not copied into the image
+    #[serde(default)]
+    fetchonly: Vec<FileEntry>,
+}
+
 #[derive(Serialize)]
 pub struct SolidFileEntry {
-    path: PathBuf,
+    #[serde(flatten)]
+    source: FileSource,
     public: bool,
     #[serde(serialize_with = "serialize_as_slash_path")]
     target_path: PathBuf,
     permissions: FilePermissions,
+    // Files owned by the problem user can be modified by the problem process
+    user_owned: bool,
+}
+
+impl FileSource {
+    pub fn file_name(&self) -> SomaResult<OsString> {
+        match self {
+            FileSource::Local { path } => Ok(path
+                .file_name()
+                .ok_or(SomaError::FileNameNotFound)?
+                .to_owned()),
+            FileSource::Remote { url, .. } => {
+                let url = Url::parse(url)?;
+                match url
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                {
+                    Some(file_name) if !file_name.is_empty() => Ok(OsString::from(file_name)),
+                    _ => Err(SomaError::FileNameNotFound)?,
+                }
+            }
+        }
+    }
+
+    // Local paths containing glob metacharacters match several files
+    pub fn is_pattern(&self) -> bool {
+        match self {
+            FileSource::Local { path } => match path.to_str() {
+                Some(path) => Pattern::escape(path) != path,
+                None => false,
+            },
+            FileSource::Remote { .. } => false,
+        }
+    }
+
+    // Patterns expand to every match in the problem directory in alphabetical order
+    pub fn expand(&self, problem_dir: impl AsRef<Path>) -> SomaResult<Vec<FileSource>> {
+        let path = match self {
+            FileSource::Local { path } if self.is_pattern() => path,
+            _ => return Ok(vec![self.clone()]),
+        };
+
+        let problem_dir = problem_dir.as_ref();
+        let problem_dir_pattern =
+            Pattern::escape(&problem_dir.to_slash().ok_or(SomaError::InvalidUnicode)?);
+        let pattern = format!(
+            "{}/{}",
+            problem_dir_pattern,
+            path.to_slash().ok_or(SomaError::InvalidUnicode)?
+        );
+
+        let sources = glob::glob(&pattern)?
+            .map(|matched_path| {
+                Ok(FileSource::Local {
+                    path: matched_path?.strip_prefix(problem_dir)?.to_owned(),
+                })
+            })
+            .collect::<SomaResult<Vec<_>>>()?;
+        if sources.is_empty() {
+            Err(SomaError::InvalidManifest(format!(
+                "path pattern '{}' does not match any file",
+                path.display()
+            )))?;
+        }
+        Ok(sources)
+    }
+
+    pub fn validate(&self) -> SomaResult<()> {
+        if let FileSource::Remote { url, sha256 } = self {
+            match Url::parse(url) {
+                Ok(ref url) if url.scheme() == "http" || url.scheme() == "https" => (),
+                _ => Err(SomaError::InvalidManifest(format!(
+                    "url '{}' should be an HTTP or HTTPS URL",
+                    url
+                )))?,
+            }
+
+            if !is_valid_sha256(sha256) {
+                Err(SomaError::InvalidManifest(format!(
+                    "sha256 of '{}' should be {} hexadecimal digits",
+                    url, SHA256_HEX_LENGTH
+                )))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl FileEntry {
-    pub fn path(&self) -> &PathBuf {
-        &self.path
+    pub fn new(path: PathBuf) -> Self {
+        FileEntry {
+            source: FileSource::Local { path },
+            public: None,
+            target_path: None,
+        }
+    }
+
+    pub fn source(&self) -> &FileSource {
+        &self.source
     }
 
     pub fn public(&self) -> bool {
@@ -107,31 +254,132 @@ impl FileEntry {
         work_dir: impl AsRef<Path>,
         permissions: FilePermissions,
     ) -> SomaResult<SolidFileEntry> {
+        self.source.validate()?;
+
         let target_path = match &self.target_path {
             Some(path) => path.clone(),
-            None => {
-                let file_name = self.path.file_name().ok_or(SomaError::FileNameNotFound)?;
-                work_dir.as_ref().join(file_name)
-            }
+            None => work_dir.as_ref().join(self.source.file_name()?),
         };
 
-        // TODO: More descriptive error
         if !target_path.has_root() {
-            Err(SomaError::InvalidManifest)?;
+            Err(SomaError::InvalidManifest(
+                "target_path should be an absolute path".to_owned(),
+            ))?;
         }
 
         Ok(SolidFileEntry {
-            path: self.path.clone(),
+            source: self.source.clone(),
             public: self.public.unwrap_or(false),
             target_path,
             permissions,
+            user_owned: permissions == FilePermissions::ReadWrite,
         })
     }
+
+    // Each match of a pattern keeps its file name under target_path or work_dir
+    pub fn expand(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+        permissions: FilePermissions,
+    ) -> SomaResult<Vec<SolidFileEntry>> {
+        if !self.source.is_pattern() {
+            return Ok(vec![self.solidify(work_dir, permissions)?]);
+        }
+
+        let target_dir = match &self.target_path {
+            Some(path) => path.as_path(),
+            None => work_dir.as_ref(),
+        };
+        self.source
+            .expand(problem_dir)?
+            .into_iter()
+            .map(|source| {
+                let file_entry = FileEntry {
+                    target_path: Some(target_dir.join(source.file_name()?)),
+                    source,
+                    public: self.public,
+                };
+                file_entry.solidify(&work_dir, permissions)
+            })
+            .collect()
+    }
+}
+
+impl FileEntryWithPermissions {
+    pub fn file_entry(&self) -> &FileEntry {
+        &self.file_entry
+    }
+
+    pub fn expand(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+    ) -> SomaResult<Vec<SolidFileEntry>> {
+        self.file_entry
+            .expand(problem_dir, work_dir, self.permissions)
+    }
+}
+
+impl FileEntries {
+    pub fn public_files(&self) -> Vec<&FileSource> {
+        let executables = self.executable.iter();
+        let readonly = self.readonly.iter();
+        let readwrite = self.readwrite.iter();
+        let with_permissions = self
+            .with_permissions
+            .iter()
+            .map(|file_entry| file_entry.file_entry());
+        let fetchonly = self.fetchonly.iter();
+
+        executables
+            .chain(readonly)
+            .chain(readwrite)
+            .chain(with_permissions)
+            .filter(|file_entry| file_entry.public())
+            .chain(fetchonly)
+            .map(|file_entry| file_entry.source())
+            .collect()
+    }
+
+    pub fn solidify(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+    ) -> SomaResult<Vec<SolidFileEntry>> {
+        let executable = self
+            .executable
+            .iter()
+            .map(|file| file.expand(&problem_dir, &work_dir, FilePermissions::Executable));
+        let readonly = self
+            .readonly
+            .iter()
+            .map(|file| file.expand(&problem_dir, &work_dir, FilePermissions::ReadOnly));
+        let readwrite = self
+            .readwrite
+            .iter()
+            .map(|file| file.expand(&problem_dir, &work_dir, FilePermissions::ReadWrite));
+        let with_permissions = self
+            .with_permissions
+            .iter()
+            .map(|file| file.expand(&problem_dir, &work_dir));
+
+        let file_entries = executable
+            .chain(readonly)
+            .chain(readwrite)
+            .chain(with_permissions)
+            .collect::<SomaResult<Vec<_>>>()?;
+        Ok(file_entries.into_iter().flatten().collect())
+    }
 }
 
 impl SolidFileEntry {
-    pub fn path_map(&self) -> (&PathBuf, &PathBuf) {
-        (&self.path, &self.target_path)
+    pub fn target_path(&self) -> &PathBuf {
+        &self.target_path
+    }
+
+    pub fn path_map(&self) -> (&FileSource, &PathBuf) {
+        (&self.source, &self.target_path)
     }
 }
 
@@ -144,6 +392,7 @@ mod tests {
     fn test_file_permissions_ser() {
         assert_ser_tokens(&FilePermissions::Executable, &[Token::Str("550")]);
         assert_ser_tokens(&FilePermissions::ReadOnly, &[Token::Str("440")]);
+        assert_ser_tokens(&FilePermissions::ReadWrite, &[Token::Str("660")]);
         assert_ser_tokens(&FilePermissions::Custom(0o777), &[Token::Str("777")]);
     }
 
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use path_slash::PathBufExt;
+use serde::{Deserialize, Serialize};
+
+use super::common::{serialize_as_slash_path, validate_relative_path, FileSource, SolidFileEntry};
+use crate::prelude::*;
+
+const DEFAULT_DOCKERFILE: &str = "Dockerfile";
+const DEFAULT_CONTEXT: &str = ".";
+
+#[derive(Deserialize)]
+pub struct DockerConfig {
+    // Relative to the build context
+    dockerfile: Option<PathBuf>,
+    // Relative to the problem directory
+    context: Option<PathBuf>,
+    port: u16,
+}
+
+#[derive(Serialize)]
+pub struct SolidDockerConfig {
+    dockerfile: String,
+    #[serde(serialize_with = "serialize_as_slash_path")]
+    context: PathBuf,
+    port: u16,
+    file_entries: Vec<SolidFileEntry>,
+}
+
+impl DockerConfig {
+    pub fn public_files(&self) -> Vec<&FileSource> {
+        Vec::new()
+    }
+
+    pub fn solidify(&self) -> SomaResult<SolidDockerConfig> {
+        let dockerfile = match &self.dockerfile {
+            Some(path) => path.clone(),
+            None => PathBuf::from(DEFAULT_DOCKERFILE),
+        };
+        let context = match &self.context {
+            Some(path) => path.clone(),
+            None => PathBuf::from(DEFAULT_CONTEXT),
+        };
+
+        validate_relative_path(&dockerfile, "dockerfile")?;
+        validate_relative_path(&context, "context")?;
+
+        Ok(SolidDockerConfig {
+            // Docker expects a slash separated path for the Dockerfile
+            dockerfile: dockerfile.to_slash().ok_or(SomaError::InvalidUnicode)?,
+            context,
+            port: self.port,
+            file_entries: Vec::new(),
+        })
+    }
+}
+
+impl SolidDockerConfig {
+    pub fn dockerfile(&self) -> &str {
+        &self.dockerfile
+    }
+
+    pub fn context(&self) -> &PathBuf {
+        &self.context
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    // Files are installed by the Dockerfile itself
+    pub fn file_entries(&self) -> &Vec<SolidFileEntry> {
+        &self.file_entries
+    }
+}
@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+
+use lazy_static::lazy_static;
+use path_slash::PathBufExt;
+use rand::Rng;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+const DEFAULT_FORMAT: &str = "FLAG{<hex32>}";
+const MAX_HEX_LENGTH: usize = 256;
+const HEX_DIGITS: &[u8] = b"0123456789abcdef";
+
+lazy_static! {
+    // Replaced with the given number of random hexadecimal digits, e.g. "<hex32>"
+    static ref HEX_PLACEHOLDER_REGEX: Regex = Regex::new(r"<hex(\d+)>").unwrap();
+}
+
+#[derive(Deserialize)]
+pub struct FlagConfig {
+    format: Option<String>,
+    path: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct SolidFlagConfig {
+    format: String,
+    path: String,
+}
+
+impl FlagConfig {
+    pub fn solidify(&self) -> SomaResult<SolidFlagConfig> {
+        let format = match &self.format {
+            Some(format) => format.clone(),
+            None => DEFAULT_FORMAT.to_owned(),
+        };
+
+        let hex_length_valid = HEX_PLACEHOLDER_REGEX
+            .captures_iter(&format)
+            .all(|captures| match captures[1].parse::<usize>() {
+                Ok(length) => length > 0 && length <= MAX_HEX_LENGTH,
+                Err(_) => false,
+            });
+        if !hex_length_valid {
+            Err(SomaError::InvalidManifest(format!(
+                "flag format '{}' should contain hex placeholders of length 1 to {}",
+                format, MAX_HEX_LENGTH
+            )))?;
+        }
+
+        if !self.path.has_root() {
+            Err(SomaError::InvalidManifest(
+                "flag path should be an absolute path".to_owned(),
+            ))?;
+        }
+
+        Ok(SolidFlagConfig {
+            format,
+            // Docker expects a slash separated path inside the container
+            path: self.path.to_slash().ok_or(SomaError::InvalidUnicode)?,
+        })
+    }
+}
+
+impl SolidFlagConfig {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn generate(&self) -> String {
+        let mut rng = rand::thread_rng();
+        HEX_PLACEHOLDER_REGEX
+            .replace_all(&self.format, |captures: &Captures| {
+                // Length is validated on solidification
+                let length: usize = captures[1].parse().unwrap();
+                (0..length)
+                    .map(|_| HEX_DIGITS[rng.gen_range(0, HEX_DIGITS.len())] as char)
+                    .collect::<String>()
+            })
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let flag_config: FlagConfig = toml::from_str(r#"path = "/flag""#).unwrap();
+        let flag_config = flag_config.solidify().unwrap();
+        let flag = flag_config.generate();
+        assert!(Regex::new(r"^FLAG\{[0-9a-f]{32}\}$")
+            .unwrap()
+            .is_match(&flag));
+        assert_ne!(flag, flag_config.generate());
+
+        let flag_config: FlagConfig = toml::from_str(
+            r#"
+            format = "soma{<hex4>_<hex8>}"
+            path = "/flag"
+            "#,
+        )
+        .unwrap();
+        let flag = flag_config.solidify().unwrap().generate();
+        assert!(Regex::new(r"^soma\{[0-9a-f]{4}_[0-9a-f]{8}\}$")
+            .unwrap()
+            .is_match(&flag));
+    }
+
+    #[test]
+    fn test_invalid_flag() {
+        let solidify = |flag: &str| toml::from_str::<FlagConfig>(flag)?.solidify();
+
+        assert!(solidify(r#"path = "flag""#).is_err());
+        assert!(solidify("format = \"FLAG{<hex0>}\"\npath = \"/flag\"").is_err());
+        assert!(solidify("format = \"FLAG{<hex1024>}\"\npath = \"/flag\"").is_err());
+        assert!(
+            solidify("format = \"FLAG{<hex99999999999999999999>}\"\npath = \"/flag\"").is_err()
+        );
+    }
+}
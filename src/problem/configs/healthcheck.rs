@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// Durations in seconds, shorter than the docker defaults so that waiting runs return early
+const DEFAULT_INTERVAL: u64 = 5;
+const DEFAULT_TIMEOUT: u64 = 5;
+const DEFAULT_START_PERIOD: u64 = 0;
+const DEFAULT_RETRIES: u64 = 3;
+
+#[derive(Deserialize)]
+pub struct HealthcheckConfig {
+    cmd: String,
+    interval: Option<u64>,
+    timeout: Option<u64>,
+    start_period: Option<u64>,
+    retries: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SolidHealthcheckConfig {
+    cmd: String,
+    interval: u64,
+    timeout: u64,
+    start_period: u64,
+    retries: u64,
+}
+
+impl HealthcheckConfig {
+    pub fn solidify(&self) -> SomaResult<SolidHealthcheckConfig> {
+        // The command is rendered into a single Dockerfile instruction
+        if self.cmd.trim().is_empty() || self.cmd.contains(['\n', '\r']) {
+            Err(SomaError::InvalidManifest(
+                "healthcheck cmd should be a non-empty single line command".to_owned(),
+            ))?;
+        }
+
+        if self.interval == Some(0) || self.timeout == Some(0) || self.retries == Some(0) {
+            Err(SomaError::InvalidManifest(
+                "healthcheck interval, timeout and retries should be positive".to_owned(),
+            ))?;
+        }
+
+        Ok(SolidHealthcheckConfig {
+            cmd: self.cmd.clone(),
+            interval: self.interval.unwrap_or(DEFAULT_INTERVAL),
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            start_period: self.start_period.unwrap_or(DEFAULT_START_PERIOD),
+            retries: self.retries.unwrap_or(DEFAULT_RETRIES),
+        })
+    }
+}
+
+impl SolidHealthcheckConfig {
+    // Looks for a listening socket instead of connecting, since each connection runs the problem
+    pub fn tcp_listen(port: u16) -> Self {
+        SolidHealthcheckConfig {
+            cmd: format!(
+                "cat /proc/net/tcp /proc/net/tcp6 2>/dev/null | grep -Eq ':{:04X} [0-9A-F]+:0000 0A '",
+                port
+            ),
+            interval: DEFAULT_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+            start_period: DEFAULT_START_PERIOD,
+            retries: DEFAULT_RETRIES,
+        }
+    }
+
+    pub fn cmd(&self) -> &str {
+        &self.cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_healthcheck() {
+        let solidify = |healthcheck: &str| {
+            toml::from_str::<HealthcheckConfig>(healthcheck)
+                .unwrap()
+                .solidify()
+        };
+
+        assert_eq!(
+            solidify("cmd = \"curl -f http://localhost/\"\ninterval = 10\nstart_period = 30")
+                .unwrap(),
+            SolidHealthcheckConfig {
+                cmd: "curl -f http://localhost/".to_owned(),
+                interval: 10,
+                timeout: DEFAULT_TIMEOUT,
+                start_period: 30,
+                retries: DEFAULT_RETRIES,
+            }
+        );
+        assert_eq!(
+            SolidHealthcheckConfig::tcp_listen(1337).cmd(),
+            "cat /proc/net/tcp /proc/net/tcp6 2>/dev/null | grep -Eq ':0539 [0-9A-F]+:0000 0A '"
+        );
+
+        assert!(solidify(r#"cmd = """#).is_err());
+        assert!(solidify(r#"cmd = "true\nfalse""#).is_err());
+        assert!(solidify("cmd = \"true\"\ninterval = 0").is_err());
+        assert!(solidify("cmd = \"true\"\nretries = 0").is_err());
+    }
+}
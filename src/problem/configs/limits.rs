@@ -0,0 +1,116 @@
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+// Size suffixes accepted by docker, such as "512m"
+const SIZE_UNITS: &[(char, u64)] = &[('b', 1), ('k', 1 << 10), ('m', 1 << 20), ('g', 1 << 30)];
+
+#[derive(Default, Deserialize)]
+pub struct LimitsConfig {
+    memory: Option<String>,
+    cpu_shares: Option<u64>,
+    pids_limit: Option<u64>,
+    nproc: Option<u64>,
+    fsize: Option<String>,
+    tmpfs: Option<String>,
+}
+
+// Every limit is left to the docker default when not specified
+#[derive(Debug, Default, PartialEq)]
+pub struct SolidLimitsConfig {
+    memory: Option<u64>,
+    cpu_shares: Option<u64>,
+    pids_limit: Option<u64>,
+    nproc: Option<u64>,
+    fsize: Option<u64>,
+    tmpfs: Option<u64>,
+}
+
+impl LimitsConfig {
+    pub fn solidify(&self) -> SomaResult<SolidLimitsConfig> {
+        Ok(SolidLimitsConfig {
+            memory: parse_size_field("memory", &self.memory)?,
+            cpu_shares: self.cpu_shares,
+            pids_limit: self.pids_limit,
+            nproc: self.nproc,
+            fsize: parse_size_field("fsize", &self.fsize)?,
+            tmpfs: parse_size_field("tmpfs", &self.tmpfs)?,
+        })
+    }
+}
+
+impl SolidLimitsConfig {
+    // Memory limit in bytes
+    pub fn memory(&self) -> Option<u64> {
+        self.memory
+    }
+
+    pub fn cpu_shares(&self) -> Option<u64> {
+        self.cpu_shares
+    }
+
+    pub fn pids_limit(&self) -> Option<u64> {
+        self.pids_limit
+    }
+
+    pub fn nproc(&self) -> Option<u64> {
+        self.nproc
+    }
+
+    // Maximum size of a file written by the container in bytes
+    pub fn fsize(&self) -> Option<u64> {
+        self.fsize
+    }
+
+    // Size of the tmpfs mounted on /tmp in bytes
+    pub fn tmpfs(&self) -> Option<u64> {
+        self.tmpfs
+    }
+}
+
+fn parse_size_field(field: &str, size: &Option<String>) -> SomaResult<Option<u64>> {
+    match size {
+        Some(size) => match parse_size(size) {
+            Some(bytes) => Ok(Some(bytes)),
+            None => Err(SomaError::InvalidManifest(format!(
+                "{} '{}' is not a valid size, expected a number with an optional b, k, m or g suffix",
+                field, size
+            )))?,
+        },
+        None => Ok(None),
+    }
+}
+
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim().to_lowercase();
+    let (number, multiplier) = match size.chars().last() {
+        Some(suffix) if suffix.is_ascii_alphabetic() => {
+            let (_, multiplier) = SIZE_UNITS.iter().find(|(unit, _)| *unit == suffix)?;
+            (&size[..size.len() - 1], *multiplier)
+        }
+        _ => (&size[..], 1),
+    };
+
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("16b"), Some(16));
+        assert_eq!(parse_size("4k"), Some(4096));
+        assert_eq!(parse_size("512m"), Some(512 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Some(2 * 1024 * 1024 * 1024));
+
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("m"), None);
+        assert_eq!(parse_size("-1"), None);
+        assert_eq!(parse_size("1.5g"), None);
+        assert_eq!(parse_size("10t"), None);
+        assert_eq!(parse_size("99999999999999999999k"), None);
+    }
+}
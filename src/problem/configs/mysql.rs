@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::common::{FileEntry, FilePermissions, FileSource, SolidFileEntry};
+use crate::prelude::*;
+
+const DEFAULT_VERSION: &str = "5.7";
+const INIT_DIR: &str = "/docker-entrypoint-initdb.d";
+
+// Used as the image name suffix and the host name seen from the problem container
+pub const MYSQL_SERVICE_NAME: &str = "mysql";
+pub const MYSQL_PORT: u16 = 3306;
+
+#[derive(Deserialize)]
+pub struct MysqlConfig {
+    version: Option<String>,
+    init: Option<PathBuf>,
+    database: String,
+    user: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+pub struct SolidMysqlConfig {
+    version: String,
+    init: Option<SolidFileEntry>,
+    database: String,
+    user: String,
+    password: String,
+}
+
+impl MysqlConfig {
+    pub fn solidify(&self) -> SomaResult<SolidMysqlConfig> {
+        // The official image runs SQL files in the init directory on its first start
+        let init = match &self.init {
+            Some(path) => {
+                Some(FileEntry::new(path.clone()).solidify(INIT_DIR, FilePermissions::ReadOnly)?)
+            }
+            None => None,
+        };
+
+        Ok(SolidMysqlConfig {
+            version: match &self.version {
+                Some(version) => version.clone(),
+                None => DEFAULT_VERSION.to_owned(),
+            },
+            init,
+            database: self.database.clone(),
+            user: self.user.clone(),
+            password: self.password.clone(),
+        })
+    }
+}
+
+impl SolidMysqlConfig {
+    pub fn path_maps(&self) -> impl Iterator<Item = (&FileSource, &PathBuf)> {
+        self.init.iter().map(SolidFileEntry::path_map)
+    }
+
+    // Environment variables for the database container
+    pub fn server_env(&self) -> BTreeMap<String, String> {
+        vec![
+            ("MYSQL_DATABASE", self.database.clone()),
+            ("MYSQL_USER", self.user.clone()),
+            ("MYSQL_PASSWORD", self.password.clone()),
+            ("MYSQL_RANDOM_ROOT_PASSWORD", "yes".to_owned()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect()
+    }
+
+    // Environment variables for the problem container
+    pub fn client_env(&self) -> BTreeMap<String, String> {
+        vec![
+            ("MYSQL_HOST", MYSQL_SERVICE_NAME.to_owned()),
+            ("MYSQL_PORT", MYSQL_PORT.to_string()),
+            ("MYSQL_DATABASE", self.database.clone()),
+            ("MYSQL_USER", self.user.clone()),
+            ("MYSQL_PASSWORD", self.password.clone()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect()
+    }
+}
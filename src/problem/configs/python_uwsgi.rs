@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use path_slash::PathBufExt;
+use serde::{Deserialize, Serialize};
+
+use super::common::{FileEntries, FileEntry, FilePermissions, FileSource, SolidFileEntry};
+use crate::prelude::*;
+
+const DEFAULT_PYTHON_VERSION: &str = "3";
+const DEFAULT_PORT: u16 = 8000;
+
+#[derive(Deserialize)]
+pub struct PythonUwsgiConfig {
+    python_version: Option<String>,
+    requirements: Option<PathBuf>,
+    module: String,
+    port: Option<u16>,
+    #[serde(flatten)]
+    file_entries: FileEntries,
+}
+
+#[derive(Serialize)]
+pub struct SolidPythonUwsgiConfig {
+    python_version: String,
+    // Path of the requirements file inside the image
+    requirements: Option<String>,
+    module: String,
+    port: u16,
+    file_entries: Vec<SolidFileEntry>,
+}
+
+impl PythonUwsgiConfig {
+    pub fn public_files(&self) -> Vec<&FileSource> {
+        self.file_entries.public_files()
+    }
+
+    pub fn solidify(
+        &self,
+        problem_dir: impl AsRef<Path>,
+        work_dir: impl AsRef<Path>,
+    ) -> SomaResult<SolidPythonUwsgiConfig> {
+        let mut file_entries = self.file_entries.solidify(problem_dir, &work_dir)?;
+
+        // Requirements file is copied into the image like other read-only files
+        let requirements = match &self.requirements {
+            Some(path) => {
+                let file_entry =
+                    FileEntry::new(path.clone()).solidify(&work_dir, FilePermissions::ReadOnly)?;
+                let target_path = file_entry
+                    .target_path()
+                    .to_slash()
+                    .ok_or(SomaError::InvalidUnicode)?;
+                file_entries.push(file_entry);
+                Some(target_path)
+            }
+            None => None,
+        };
+
+        Ok(SolidPythonUwsgiConfig {
+            python_version: match &self.python_version {
+                Some(version) => version.clone(),
+                None => DEFAULT_PYTHON_VERSION.to_owned(),
+            },
+            requirements,
+            module: self.module.clone(),
+            port: self.port.unwrap_or(DEFAULT_PORT),
+            file_entries,
+        })
+    }
+}
+
+impl SolidPythonUwsgiConfig {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn file_entries(&self) -> &Vec<SolidFileEntry> {
+        &self.file_entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+
+    use super::*;
+
+    #[test]
+    fn test_python_uwsgi() {
+        let config: PythonUwsgiConfig = toml::from_str(
+            r#"
+            requirements = "requirements.txt"
+            module = "app:app"
+
+            [[readonly]]
+            path = "app.py"
+            "#,
+        )
+        .unwrap();
+        let config = config.solidify(".", "/home/flask").unwrap();
+        assert_eq!(config.port(), 8000);
+        assert_eq!(config.file_entries().len(), 2);
+
+        let rendered = Handlebars::new()
+            .render_template("{{ python_version }};{{ requirements }}", &config)
+            .unwrap();
+        assert_eq!(rendered, "3;/home/flask/requirements.txt");
+    }
+}
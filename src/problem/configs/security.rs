@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use path_slash::PathBufExt;
+use serde::Deserialize;
+
+use super::common::validate_relative_path;
+use crate::prelude::*;
+
+// Scratch directory of a read-only container when none is specified
+const DEFAULT_TMPFS: &str = "/tmp";
+
+#[derive(Default, Deserialize)]
+pub struct SecurityConfig {
+    // Every other capability is dropped when specified
+    capabilities: Option<Vec<String>>,
+    // Relative to the problem directory
+    seccomp: Option<PathBuf>,
+    #[serde(default)]
+    no_new_privileges: bool,
+    #[serde(default)]
+    read_only: bool,
+    tmpfs: Option<Vec<PathBuf>>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SolidSecurityConfig {
+    capabilities: Option<Vec<String>>,
+    seccomp: Option<PathBuf>,
+    no_new_privileges: bool,
+    read_only: bool,
+    tmpfs: Vec<String>,
+}
+
+impl SecurityConfig {
+    pub fn solidify(&self) -> SomaResult<SolidSecurityConfig> {
+        // Docker accepts capability names with or without "CAP_" prefix
+        let capabilities = match &self.capabilities {
+            Some(capabilities) => {
+                let capabilities: Vec<String> = capabilities
+                    .iter()
+                    .map(|capability| capability.to_uppercase())
+                    .collect();
+                if let Some(capability) = capabilities
+                    .iter()
+                    .find(|capability| !is_valid_capability(capability))
+                {
+                    Err(SomaError::InvalidManifest(format!(
+                        "capability '{}' is invalid",
+                        capability
+                    )))?;
+                }
+                Some(capabilities)
+            }
+            None => None,
+        };
+
+        if let Some(seccomp) = &self.seccomp {
+            validate_relative_path(seccomp, "seccomp")?;
+        }
+
+        let tmpfs = match &self.tmpfs {
+            Some(tmpfs) => tmpfs.clone(),
+            None if self.read_only => vec![PathBuf::from(DEFAULT_TMPFS)],
+            None => Vec::new(),
+        };
+        if tmpfs.iter().any(|path| !path.has_root()) {
+            Err(SomaError::InvalidManifest(
+                "tmpfs should contain absolute paths".to_owned(),
+            ))?;
+        }
+
+        Ok(SolidSecurityConfig {
+            capabilities,
+            seccomp: self.seccomp.clone(),
+            no_new_privileges: self.no_new_privileges,
+            read_only: self.read_only,
+            // Docker expects slash separated paths inside the container
+            tmpfs: tmpfs
+                .iter()
+                .map(|path| path.to_slash().ok_or(SomaError::InvalidUnicode))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl SolidSecurityConfig {
+    pub fn capabilities(&self) -> Option<&Vec<String>> {
+        self.capabilities.as_ref()
+    }
+
+    // Seccomp profile relative to the problem directory
+    pub fn seccomp(&self) -> Option<&PathBuf> {
+        self.seccomp.as_ref()
+    }
+
+    pub fn no_new_privileges(&self) -> bool {
+        self.no_new_privileges
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn tmpfs(&self) -> &Vec<String> {
+        &self.tmpfs
+    }
+}
+
+fn is_valid_capability(capability: &str) -> bool {
+    !capability.is_empty()
+        && capability
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_security() {
+        let solidify = |security: &str| toml::from_str::<SecurityConfig>(security)?.solidify();
+
+        assert!(solidify(r#"capabilities = ["NET_BIND_SERVICE"]"#).is_ok());
+        assert!(solidify(r#"capabilities = ["NET BIND"]"#).is_err());
+        assert!(solidify(r#"capabilities = [""]"#).is_err());
+        assert!(solidify(r#"seccomp = "/etc/seccomp.json""#).is_err());
+        assert!(solidify(r#"seccomp = "../seccomp.json""#).is_err());
+        assert!(solidify(r#"tmpfs = ["tmp"]"#).is_err());
+    }
+}
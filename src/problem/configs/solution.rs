@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::common::FileSource;
+use crate::prelude::*;
+
+// The solver container reaches the problem with this host name
+pub const SOLVER_SERVICE_NAME: &str = "solver";
+
+const SOLUTION_DIR: &str = "/solution";
+const DEFAULT_TIMEOUT: u64 = 60;
+
+#[derive(Deserialize)]
+pub struct SolutionConfig {
+    image: String,
+    script: PathBuf,
+    cmd: Option<String>,
+    flag: Option<String>,
+    regex: Option<String>,
+    // Seconds to wait for the solver to exit
+    timeout: Option<u64>,
+}
+
+// Solvers pass when their output contains the expected flag
+enum ExpectedOutput {
+    Flag(String),
+    Regex(Regex),
+    GeneratedFlag,
+}
+
+#[derive(Serialize)]
+pub struct SolidSolutionConfig {
+    image: String,
+    cmd: String,
+    work_dir: String,
+    #[serde(skip)]
+    script: FileSource,
+    #[serde(skip)]
+    target_path: PathBuf,
+    #[serde(skip)]
+    expected: ExpectedOutput,
+    #[serde(skip)]
+    timeout: u64,
+}
+
+impl SolutionConfig {
+    // Without an expected output, the solver should print the flag generated for the run
+    pub fn solidify(&self, generates_flag: bool) -> SomaResult<SolidSolutionConfig> {
+        let script = FileSource::Local {
+            path: self.script.clone(),
+        };
+        let file_name = script.file_name()?;
+        let target_path = Path::new(SOLUTION_DIR).join(&file_name);
+
+        let cmd = match &self.cmd {
+            Some(cmd) => cmd.clone(),
+            None => format!(
+                "\"./{}\"",
+                file_name.to_str().ok_or(SomaError::InvalidUnicode)?
+            ),
+        };
+        // The command is rendered into a single Dockerfile instruction
+        if cmd.trim().is_empty() || cmd.contains(['\n', '\r']) {
+            Err(SomaError::InvalidManifest(
+                "solution cmd should be a non-empty single line command".to_owned(),
+            ))?;
+        }
+
+        if self.timeout == Some(0) {
+            Err(SomaError::InvalidManifest(
+                "solution timeout should be positive".to_owned(),
+            ))?;
+        }
+
+        let expected = match (&self.flag, &self.regex) {
+            (Some(_), Some(_)) => Err(SomaError::InvalidManifest(
+                "solution should have either flag or regex, not both".to_owned(),
+            ))?,
+            (Some(flag), None) => ExpectedOutput::Flag(flag.clone()),
+            (None, Some(regex)) => match Regex::new(regex) {
+                Ok(regex) => ExpectedOutput::Regex(regex),
+                Err(_) => Err(SomaError::InvalidManifest(format!(
+                    "solution regex '{}' is invalid",
+                    regex
+                )))?,
+            },
+            (None, None) if generates_flag => ExpectedOutput::GeneratedFlag,
+            (None, None) => Err(SomaError::InvalidManifest(
+                "solution should have flag or regex unless the problem has a flag section"
+                    .to_owned(),
+            ))?,
+        };
+
+        Ok(SolidSolutionConfig {
+            image: self.image.clone(),
+            cmd,
+            work_dir: SOLUTION_DIR.to_owned(),
+            script,
+            target_path,
+            expected,
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+        })
+    }
+}
+
+impl SolidSolutionConfig {
+    pub fn path_map(&self) -> (&FileSource, &PathBuf) {
+        (&self.script, &self.target_path)
+    }
+
+    pub fn cmd(&self) -> &str {
+        &self.cmd
+    }
+
+    pub fn timeout(&self) -> u64 {
+        self.timeout
+    }
+
+    pub fn output_matches(&self, output: &str, generated_flag: Option<&str>) -> bool {
+        match &self.expected {
+            ExpectedOutput::Flag(flag) => output.contains(flag.as_str()),
+            ExpectedOutput::Regex(regex) => regex.is_match(output),
+            ExpectedOutput::GeneratedFlag => match generated_flag {
+                Some(flag) => output.contains(flag),
+                None => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+
+    use super::*;
+
+    fn solidify(solution: &str, generates_flag: bool) -> SomaResult<SolidSolutionConfig> {
+        toml::from_str::<SolutionConfig>(solution)?.solidify(generates_flag)
+    }
+
+    #[test]
+    fn test_solution() {
+        let solution = solidify(
+            "image = \"alpine:3.9\"\nscript = \"solve.sh\"\ntimeout = 30",
+            true,
+        )
+        .unwrap();
+        assert_eq!(solution.cmd(), "\"./solve.sh\"");
+        assert_eq!(solution.timeout(), 30);
+        assert_eq!(
+            solution.path_map(),
+            (
+                &FileSource::Local {
+                    path: PathBuf::from("solve.sh")
+                },
+                &PathBuf::from("/solution/solve.sh")
+            )
+        );
+
+        // Solvers of a problem with a flag section should print the generated flag
+        assert!(solution.output_matches("flag: CTF{1234}\n", Some("CTF{1234}")));
+        assert!(!solution.output_matches("flag: CTF{5678}\n", Some("CTF{1234}")));
+        assert!(!solution.output_matches("flag: CTF{1234}\n", None));
+
+        let dockerfile = Handlebars::new()
+            .render_template(
+                include_str!("../../../templates/solver/Dockerfile"),
+                &solution,
+            )
+            .unwrap();
+        assert!(dockerfile.starts_with("FROM alpine:3.9"));
+        assert!(dockerfile.contains("CMD \"./solve.sh\""));
+
+        let solution_config = |solution: &str| {
+            solidify(
+                &format!(
+                    "image = \"python:3\"\nscript = \"exploit/solve.py\"\n{}",
+                    solution
+                ),
+                false,
+            )
+        };
+
+        let solution = solution_config(r#"regex = 'flag\{[0-9a-f]+\}'"#).unwrap();
+        assert!(solution.output_matches("flag{c0ffee}", None));
+        assert!(!solution.output_matches("flag{}", None));
+
+        let solution =
+            solution_config("cmd = \"python3 solve.py\"\nflag = \"flag{static}\"").unwrap();
+        assert_eq!(solution.cmd(), "python3 solve.py");
+        assert_eq!(solution.timeout(), 60);
+        assert!(solution.output_matches("[+] flag{static}", None));
+
+        // The expected output should be specified without a flag section
+        assert!(solution_config("").is_err());
+        assert!(solution_config("flag = \"flag{a}\"\nregex = \"flag\"").is_err());
+        assert!(solution_config(r#"regex = 'flag{['"#).is_err());
+        assert!(solution_config("flag = \"flag{a}\"\ntimeout = 0").is_err());
+        assert!(solution_config("flag = \"flag{a}\"\ncmd = \"./a\\n./b\"").is_err());
+    }
+}
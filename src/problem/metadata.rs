@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+// Descriptive fields kept in the repository index, so that problems can be browsed
+// without reading every manifest
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Metadata {
+    // Markdown text shown by soma info
+    description: Option<String>,
+    category: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    author: Option<String>,
+    points: Option<u32>,
+    // The CTF event where the problem was originally used
+    event: Option<String>,
+    year: Option<u16>,
+}
+
+impl Metadata {
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn points(&self) -> Option<u32> {
+        self.points
+    }
+
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    pub fn year(&self) -> Option<u16> {
+        self.year
+    }
+
+    // Categories and tags are matched case-insensitively
+    pub fn has_category(&self, category: &str) -> bool {
+        match &self.category {
+            Some(own_category) => own_category.eq_ignore_ascii_case(category),
+            None => false,
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|own_tag| own_tag.eq_ignore_ascii_case(tag))
+    }
+}
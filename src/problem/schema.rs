@@ -0,0 +1,96 @@
+use toml::value::{Table, Value};
+
+use crate::prelude::*;
+
+pub const SCHEMA_KEY: &str = "schema";
+// Manifests without the schema key were written before versioning
+const LEGACY_SCHEMA: usize = 1;
+
+// Upgrades a manifest table of some schema to the next schema in place
+type Migration = fn(&mut Table) -> SomaResult<()>;
+
+// The n-th migration upgrades schema n + 1 to n + 2, append one for each format change
+const MIGRATIONS: &[Migration] = &[];
+
+pub fn migrate(manifest: &mut Value) -> SomaResult<()> {
+    apply_migrations(manifest, MIGRATIONS)
+}
+
+fn apply_migrations(manifest: &mut Value, migrations: &[Migration]) -> SomaResult<()> {
+    let table = manifest
+        .as_table_mut()
+        .ok_or_else(|| SomaError::InvalidManifest("manifest should be a TOML table".to_owned()))?;
+
+    let schema = match table.remove(SCHEMA_KEY) {
+        Some(Value::Integer(schema)) if schema >= LEGACY_SCHEMA as i64 => schema as usize,
+        Some(_) => Err(SomaError::InvalidManifest(
+            "schema should be a positive integer".to_owned(),
+        ))?,
+        None => LEGACY_SCHEMA,
+    };
+
+    let current_schema = LEGACY_SCHEMA + migrations.len();
+    if schema > current_schema {
+        Err(SomaError::UnsupportedSchema(schema, current_schema))?;
+    }
+
+    migrations[schema - LEGACY_SCHEMA..]
+        .iter()
+        .try_for_each(|migration| migration(table))
+}
+
+#[cfg(test)]
+mod tests {
+    use matches::assert_matches;
+
+    use super::*;
+
+    // Stand-in for a format change renaming "cmd" to "command"
+    fn rename_cmd(table: &mut Table) -> SomaResult<()> {
+        if let Some(cmd) = table.remove("cmd") {
+            table.insert("command".to_owned(), cmd);
+        }
+        Ok(())
+    }
+
+    fn current_schema() -> usize {
+        LEGACY_SCHEMA + MIGRATIONS.len()
+    }
+
+    fn parse(manifest: &str) -> Value {
+        toml::from_str(manifest).unwrap()
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut manifest = parse("name = \"legacy\"");
+        assert!(migrate(&mut manifest).is_ok());
+        assert_eq!(manifest, parse("name = \"legacy\""));
+
+        let mut manifest = parse(&format!("schema = {}\nname = \"a\"", current_schema()));
+        assert!(migrate(&mut manifest).is_ok());
+        assert_eq!(manifest, parse("name = \"a\""));
+
+        let migrations: &[Migration] = &[rename_cmd];
+        let mut manifest = parse("cmd = \"./a\"");
+        assert!(apply_migrations(&mut manifest, migrations).is_ok());
+        assert_eq!(manifest, parse("command = \"./a\""));
+
+        // Manifests already in the latest schema are not migrated again
+        let mut manifest = parse("schema = 2\ncmd = \"./a\"");
+        assert!(apply_migrations(&mut manifest, migrations).is_ok());
+        assert_eq!(manifest, parse("cmd = \"./a\""));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        let mut manifest = parse(&format!("schema = {}", current_schema() + 1));
+        assert_matches!(
+            migrate(&mut manifest).map_err(|error| error.downcast::<SomaError>()),
+            Err(Ok(SomaError::UnsupportedSchema(_, _)))
+        );
+
+        assert!(migrate(&mut parse("schema = 0")).is_err());
+        assert!(migrate(&mut parse("schema = \"1\"")).is_err());
+    }
+}
@@ -2,10 +2,11 @@ use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use toml::value::Table;
 
 use crate::docker::{self, SomaImage};
 use crate::prelude::*;
-use crate::problem::{read_manifest, MANIFEST_FILE_NAME};
+use crate::problem::{read_manifest, Metadata, MANIFEST_FILE_NAME};
 use crate::repository::backend::{Backend, BackendExt};
 use crate::{read_file_contents, NameString};
 
@@ -19,6 +20,9 @@ const LIST_FILE_NAME: &str = "soma-list.toml";
 #[derive(Deserialize)]
 struct ProblemList {
     problems: Vec<PathBuf>,
+    // Inherited by every problem manifest in the list
+    #[serde(default)]
+    defaults: Table,
 }
 
 impl ProblemList {
@@ -42,6 +46,9 @@ impl ProblemList {
 struct ProblemIndex {
     name: NameString,
     path: PathBuf,
+    // Indices written by older versions do not have metadata
+    #[serde(default)]
+    metadata: Metadata,
 }
 
 pub struct Repository<'a> {
@@ -120,11 +127,18 @@ impl<'a> Repository<'a> {
     pub fn prob_name_iter(&'a self) -> impl Iterator<Item = &'a NameString> {
         self.prob_list.iter().map(|prob_index| &prob_index.name)
     }
+
+    pub fn prob_metadata_iter(&'a self) -> impl Iterator<Item = (&'a NameString, &'a Metadata)> {
+        self.prob_list
+            .iter()
+            .map(|prob_index| (&prob_index.name, &prob_index.metadata))
+    }
 }
 
 fn read_prob_manifest(
     repo_path: impl AsRef<Path>,
     prob_relative_path: impl AsRef<Path>,
+    defaults: &Table,
 ) -> SomaResult<ProblemIndex> {
     let prob_path = repo_path.as_ref().join(&prob_relative_path);
     let manifest_path = prob_path.join(MANIFEST_FILE_NAME);
@@ -132,10 +146,11 @@ fn read_prob_manifest(
         Err(SomaError::InvalidRepository)?;
     }
 
-    let manifest = read_manifest(manifest_path)?;
+    let manifest = read_manifest(manifest_path, defaults)?;
     Ok(ProblemIndex {
         name: manifest.name().clone(),
         path: prob_relative_path.as_ref().to_owned(),
+        metadata: manifest.metadata().clone(),
     })
 }
 
@@ -147,9 +162,21 @@ fn read_prob_list(repo_path: impl AsRef<Path>) -> SomaResult<Vec<ProblemIndex>>
         prob_list
             .problems
             .iter()
-            .map(|prob_relative_path| read_prob_manifest(&repo_path, prob_relative_path))
+            .map(|prob_relative_path| {
+                read_prob_manifest(&repo_path, prob_relative_path, &prob_list.defaults)
+            })
             .collect()
     } else {
-        Ok(vec![read_prob_manifest(&repo_path, "./")?])
+        Ok(vec![read_prob_manifest(&repo_path, "./", &Table::new())?])
+    }
+}
+
+pub(crate) fn read_list_defaults(repo_path: impl AsRef<Path>) -> SomaResult<Table> {
+    let list_path = repo_path.as_ref().join(LIST_FILE_NAME);
+    if list_path.exists() {
+        let prob_list: ProblemList = toml::from_slice(&read_file_contents(list_path)?)?;
+        Ok(prob_list.defaults)
+    } else {
+        Ok(Table::new())
     }
 }
@@ -144,10 +144,14 @@ impl<'a> RepositoryManager<'a> {
     pub fn list_prob(&self) -> impl Iterator<Item = Problem> + '_ {
         self.repo_index.iter().flat_map(move |(repo_name, index)| {
             index.prob_list.iter().map(move |prob_index| {
+                let repo_path = self.repo_path(repo_name);
+                let prob_path = repo_path.join(&prob_index.path);
                 Problem::new(
                     repo_name.to_owned(),
                     prob_index.name.to_owned(),
-                    self.repo_path(repo_name).join(&prob_index.path),
+                    repo_path,
+                    prob_path,
+                    prob_index.metadata.clone(),
                 )
             })
         })
@@ -7,23 +7,106 @@ use serde::Serialize;
 use crate::prelude::*;
 
 pub enum Templates {
-    Binary,
+    BinarySocat,
+    BinaryXinetd,
+    BinaryNsjail,
+    ApachePhp7,
+    PythonUwsgi,
+    Mysql,
+    // Forwards published ports into an isolated network
+    Proxy,
+    // Runs the solution of a problem against its containers
+    Solver,
 }
 
 impl Templates {
     fn templates(&self) -> &[(&str, &str)] {
         match self {
-            Templates::Binary => &[
-                ("Dockerfile", include_str!("../templates/binary/Dockerfile")),
+            Templates::BinarySocat => &[
+                (
+                    "Dockerfile",
+                    include_str!("../templates/binary/socat/Dockerfile"),
+                ),
+                (
+                    ".soma/start.sh",
+                    include_str!("../templates/binary/socat/start.sh"),
+                ),
+                (
+                    ".soma/configure_permissions.sh",
+                    include_str!("../templates/binary/configure_permissions.sh"),
+                ),
+            ],
+            Templates::BinaryXinetd => &[
+                (
+                    "Dockerfile",
+                    include_str!("../templates/binary/xinetd/Dockerfile"),
+                ),
+                (
+                    ".soma/start.sh",
+                    include_str!("../templates/binary/xinetd/start.sh"),
+                ),
+                (
+                    ".soma/run.sh",
+                    include_str!("../templates/binary/xinetd/run.sh"),
+                ),
+                (
+                    ".soma/xinetd.conf",
+                    include_str!("../templates/binary/xinetd/xinetd.conf"),
+                ),
+                (
+                    ".soma/configure_permissions.sh",
+                    include_str!("../templates/binary/configure_permissions.sh"),
+                ),
+            ],
+            Templates::BinaryNsjail => &[
+                (
+                    "Dockerfile",
+                    include_str!("../templates/binary/nsjail/Dockerfile"),
+                ),
                 (
                     ".soma/start.sh",
-                    include_str!("../templates/binary/start.sh"),
+                    include_str!("../templates/binary/nsjail/start.sh"),
                 ),
                 (
                     ".soma/configure_permissions.sh",
                     include_str!("../templates/binary/configure_permissions.sh"),
                 ),
             ],
+            Templates::ApachePhp7 => &[
+                (
+                    "Dockerfile",
+                    include_str!("../templates/apache-php7/Dockerfile"),
+                ),
+                (
+                    ".soma/vhost.conf",
+                    include_str!("../templates/apache-php7/vhost.conf"),
+                ),
+                (
+                    ".soma/php.ini",
+                    include_str!("../templates/apache-php7/php.ini"),
+                ),
+                (
+                    ".soma/configure_permissions.sh",
+                    include_str!("../templates/apache-php7/configure_permissions.sh"),
+                ),
+            ],
+            Templates::PythonUwsgi => &[
+                (
+                    "Dockerfile",
+                    include_str!("../templates/python-uwsgi/Dockerfile"),
+                ),
+                (
+                    ".soma/uwsgi.ini",
+                    include_str!("../templates/python-uwsgi/uwsgi.ini"),
+                ),
+                (
+                    ".soma/configure_permissions.sh",
+                    include_str!("../templates/python-uwsgi/configure_permissions.sh"),
+                ),
+            ],
+            Templates::Mysql => &[("Dockerfile", include_str!("../templates/mysql/Dockerfile"))],
+            Templates::Proxy => &[("Dockerfile", include_str!("../templates/proxy/Dockerfile"))],
+            Templates::Solver => &[("Dockerfile", include_str!("../templates/solver/Dockerfile"))],
         }
     }
 }
@@ -0,0 +1,15 @@
+#!/bin/sh
+set -e
+
+mkdir -p "{{ apache_php7.document_root }}"
+chown -R root:www-data "{{ apache_php7.document_root }}"
+
+{{ #each apache_php7.file_entries }}
+{{ #if user_owned }}
+chown -R www-data:www-data "{{ target_path }}"
+{{ /if }}
+chmod -R {{ permissions }} "{{ target_path }}"
+if [ -d "{{ target_path }}" ]; then
+    find "{{ target_path }}" -type d -exec chmod ug+x {} +
+fi
+{{ /each }}
@@ -1,9 +1,17 @@
 #!/bin/sh
 set -e
 
+chown -R root:$PROB /home/$PROB
+
 {{ #each binary.file_entries }}
-chmod {{ permissions }} "{{ target_path }}"
+{{ #if user_owned }}
+chown -R $PROB:$PROB "{{ target_path }}"
+{{ /if }}
+chmod -R {{ permissions }} "{{ target_path }}"
+if [ -d "{{ target_path }}" ]; then
+    find "{{ target_path }}" -type d -exec chmod ug+x {} +
+fi
+{{ /each }}
+{{ #each binary.build.outputs }}
+chmod 550 "{{ target_path }}"
 {{ /each }}
-
-chown -R root:$PROB /home/$PROB
-chmod 555 /.soma/start.sh
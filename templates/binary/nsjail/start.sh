@@ -0,0 +1,12 @@
+#!/bin/sh
+# The problem user only exists in the root file system of the problem
+PROB_UID=$(awk -F: -v user="$PROB" '$1 == user { print $3 }' /chroot/etc/passwd)
+PROB_GID=$(awk -F: -v user="$PROB" '$1 == user { print $4 }' /chroot/etc/passwd)
+
+exec nsjail --mode l --port {{ binary.port }} \
+    --chroot /chroot --rw --cwd "{{ work_dir }}" \
+    --user "$PROB_UID" --group "$PROB_GID" \
+    --hostname "{{ name }}" --keep_env \
+    --time_limit {{ #if binary.timeout }}{{ binary.timeout }}{{ else }}0{{ /if }} \
+    -R /dev/urandom -R /dev/zero -B /dev/null \
+    -- /bin/sh -c "export TERM=xterm; exec {{ binary.cmd }}"
@@ -0,0 +1,5 @@
+#!/bin/sh
+export TERM=xterm
+# timeout kills the process group of each connection when the time is up
+socat {{ #if binary.idle_timeout }}-T {{ binary.idle_timeout }} {{ /if }}tcp-listen:{{ binary.port }},pktinfo,reuseaddr,fork exec:"{{ #if binary.timeout }}timeout -s KILL {{ binary.timeout }} {{ /if }}{{ binary.cmd }}",pty,ctty,raw,echo=0,stderr
+sleep infinity;
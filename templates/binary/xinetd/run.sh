@@ -0,0 +1,5 @@
+#!/bin/sh
+. /tmp/soma.env
+export TERM=xterm
+cd "{{ work_dir }}"
+exec {{ #if binary.timeout }}timeout -s KILL {{ binary.timeout }} {{ /if }}{{ binary.cmd }}
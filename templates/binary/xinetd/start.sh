@@ -0,0 +1,7 @@
+#!/bin/sh
+# Xinetd does not pass the container environment to the server,
+# and /tmp stays writable with the default scratch directory of a read-only container
+unset HOME
+export -p > /tmp/soma.env
+chmod 444 /tmp/soma.env
+exec xinetd -dontfork -f /.soma/xinetd.conf
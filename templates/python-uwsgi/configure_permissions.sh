@@ -0,0 +1,16 @@
+#!/bin/sh
+set -e
+
+chown -R root:$PROB /home/$PROB
+
+{{ #each python_uwsgi.file_entries }}
+{{ #if user_owned }}
+chown -R $PROB:$PROB "{{ target_path }}"
+{{ /if }}
+chmod -R {{ permissions }} "{{ target_path }}"
+if [ -d "{{ target_path }}" ]; then
+    find "{{ target_path }}" -type d -exec chmod ug+x {} +
+fi
+{{ /each }}
+
+chmod 444 /.soma/uwsgi.ini
@@ -0,0 +1,8 @@
+#include <stdio.h>
+
+void echo(void) {
+    char buf[64];
+    if (fgets(buf, sizeof(buf), stdin) != NULL) {
+        fputs(buf, stdout);
+    }
+}
@@ -0,0 +1,10 @@
+#include <stdio.h>
+
+void echo(void);
+
+int main(void) {
+    setvbuf(stdout, NULL, _IONBF, 0);
+    puts("Built from source");
+    echo();
+    return 0;
+}
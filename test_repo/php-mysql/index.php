@@ -0,0 +1,10 @@
+<?php
+$db = new mysqli(
+    getenv('MYSQL_HOST'),
+    getenv('MYSQL_USER'),
+    getenv('MYSQL_PASSWORD'),
+    getenv('MYSQL_DATABASE')
+);
+foreach ($db->query('SELECT title FROM posts') as $row) {
+    echo htmlspecialchars($row['title']), "\n";
+}
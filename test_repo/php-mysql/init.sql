@@ -0,0 +1,6 @@
+CREATE TABLE posts (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    title VARCHAR(64) NOT NULL
+);
+
+INSERT INTO posts (title) VALUES ('hello');
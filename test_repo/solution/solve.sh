@@ -0,0 +1,2 @@
+#!/bin/sh
+nc "$TARGET_HOST" "$TARGET_PORT"
@@ -0,0 +1,2 @@
+<?php
+echo file_get_contents("http://api:8000/");
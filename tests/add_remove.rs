@@ -1,6 +1,6 @@
 use matches::assert_matches;
 
-use soma::ops::{add, remove};
+use soma::ops::{add, info, remove};
 use soma::prelude::*;
 
 pub use self::common::*;
@@ -110,3 +110,53 @@ fn test_soma_list() {
     assert!(remove(&mut env, repo_name, &mut runtime).is_ok());
     assert!(!env.repo_manager().repo_exists(repo_name));
 }
+
+#[test]
+fn test_add_list_defaults() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+
+    // Problems relying on the defaults of soma-list.toml should be accepted
+    assert!(add(&mut env, "test_repo/defaults", None).is_ok());
+    for prob_query in &["plain", "custom"] {
+        let problem = env
+            .repo_manager()
+            .search_prob(prob_query)
+            .expect("Problem not found");
+        let manifest = problem.load_manifest().unwrap();
+        assert!(manifest.solidify(problem.path()).is_ok());
+    }
+}
+
+#[test]
+fn test_add_metadata() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+
+    // Metadata is kept in the repository index
+    assert!(add(&mut env, "test_repo/metadata", None).is_ok());
+    assert!(info(&env, "nonexistent").is_err());
+
+    let heap = info(&env, "metadata.heap").unwrap();
+    assert_eq!(heap.category(), Some("pwn"));
+    assert_eq!(heap.points(), Some(300));
+    assert_eq!(heap.author(), Some("soma"));
+    assert_eq!(
+        heap.tags(),
+        &vec!["heap".to_owned(), "use-after-free".to_owned()]
+    );
+    assert!(heap.has_tag("Heap"));
+    assert!(heap
+        .description()
+        .unwrap()
+        .starts_with("Allocate, free, and **allocate again**."));
+
+    // Defaults of soma-list.toml apply to the metadata as well
+    assert_eq!(heap.event(), Some("Soma CTF"));
+    assert_eq!(heap.year(), Some(2019));
+    let sqli = info(&env, "sqli").unwrap();
+    assert!(sqli.has_category("WEB"));
+    assert_eq!(sqli.year(), Some(2018));
+    assert!(sqli.tags().is_empty());
+    assert_eq!(sqli.description(), None);
+}
@@ -63,3 +63,50 @@ fn test_build_clean_list() {
     assert!(!image_from_repo_exists(&images, repo_name));
     assert!(!image_from_prob_exists(&images, &problem));
 }
+
+// Adds a local repository, then builds and cleans one of its problems
+fn build_clean_roundtrip(repo_path: &str, repo_name: Option<&str>, prob_query: &str) {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, repo_path, repo_name).is_ok());
+
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+    let image_name = problem.docker_image_name(env.username());
+
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(image_exists(&images, &image_name));
+    assert!(image_from_prob_exists(&images, &problem));
+
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(!image_exists(&images, &image_name));
+    assert!(!image_from_prob_exists(&images, &problem));
+}
+
+#[test]
+fn test_build_clean_apache_php7() {
+    build_clean_roundtrip("test_repo/apache-php7", Some("web"), "web.apache-php7");
+}
+
+#[test]
+fn test_build_clean_docker() {
+    build_clean_roundtrip("test_repo/docker", Some("custom"), "custom.docker");
+}
+
+// Glob patterns and overlapping directories should not prevent the build
+#[test]
+fn test_build_clean_glob() {
+    build_clean_roundtrip("test_repo/glob", None, "glob");
+}
+
+// The executable is compiled in the builder stage
+#[test]
+fn test_build_clean_source() {
+    build_clean_roundtrip("test_repo/build", None, "build");
+}
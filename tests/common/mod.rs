@@ -4,7 +4,6 @@ use std::fs;
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use hyper::client::connect::Connect;
 use tempfile::TempDir;
 use tokio::runtime::current_thread::Runtime;
 
@@ -24,7 +23,7 @@ pub const SIMPLE_BOF_REPO_NAME: &str = "simple-bof";
 pub const BATA_LIST_GIT: &str = "https://github.com/PLUS-POSTECH/soma-bata-list.git";
 pub const BATA_LIST_REPO_NAME: &str = "soma-bata-list";
 
-pub fn test_env(data_dir: &mut DataDirectory) -> Environment<impl Connect, TestPrinter> {
+pub fn test_env(data_dir: &mut DataDirectory) -> Environment<TestPrinter> {
     Environment::new(
         format!("soma-test-{}", COUNTER.fetch_add(1, Ordering::SeqCst)),
         data_dir,
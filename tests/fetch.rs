@@ -1,4 +1,15 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use matches::assert_matches;
+use sha2::{Digest, Sha256};
+
 use soma::ops::{add, fetch};
+use soma::prelude::*;
 
 pub use self::common::*;
 
@@ -10,9 +21,10 @@ fn test_fetch1() {
 
     let (_, mut data_dir) = temp_data_dir();
     let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
 
     assert!(add(&mut env, SIMPLE_BOF_GIT, None).is_ok());
-    assert!(fetch(&env, "simple-bof", &temp_copy_dir).is_ok());
+    assert!(fetch(&env, "simple-bof", &temp_copy_dir, &mut runtime).is_ok());
 
     expect_dir_contents(&temp_copy_dir, &["simple-bof"]);
 }
@@ -23,9 +35,10 @@ fn test_fetch2() {
 
     let (_, mut data_dir) = temp_data_dir();
     let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
 
     assert!(add(&mut env, BATA_LIST_GIT, None).is_ok());
-    assert!(fetch(&env, "xkcd", &temp_copy_dir).is_ok());
+    assert!(fetch(&env, "xkcd", &temp_copy_dir, &mut runtime).is_ok());
 
     expect_dir_contents(&temp_copy_dir, &["xkcd"]);
 }
@@ -36,14 +49,110 @@ fn test_fetch3() {
 
     let (_, mut data_dir) = temp_data_dir();
     let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
 
     assert!(add(&mut env, BATA_LIST_GIT, None).is_ok());
     assert!(fetch(
         &env,
         &format!("{}.r0pbaby", BATA_LIST_REPO_NAME),
-        &temp_copy_dir
+        &temp_copy_dir,
+        &mut runtime
     )
     .is_ok());
 
     expect_dir_contents(&temp_copy_dir, &["r0pbaby"]);
 }
+
+// Serves the contents to every request and counts the requests
+fn serve_file(contents: &'static [u8]) -> (u16, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let request_count = Arc::new(AtomicUsize::new(0));
+
+    let counter = request_count.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let length = stream.read(&mut buffer).unwrap();
+                if length == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..length]);
+            }
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                contents.len()
+            )
+            .unwrap();
+            stream.write_all(contents).unwrap();
+        }
+    });
+
+    (port, request_count)
+}
+
+#[test]
+fn test_fetch_remote() {
+    const CONTENTS: &[u8] = b"remote libc";
+
+    let project_dir = tempdir();
+    let temp_copy_dir1 = tempdir();
+    let temp_copy_dir2 = tempdir();
+
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    let (port, request_count) = serve_file(CONTENTS);
+    let url = format!("http://127.0.0.1:{}/files/libc.so.6", port);
+    let sha256 = format!("{:x}", Sha256::digest(CONTENTS));
+    fs::write(
+        project_dir.path().join("soma.toml"),
+        format!(
+            r#"
+            name = "remote"
+
+            [binary]
+            os = "ubuntu:16.04"
+            cmd = "./remote"
+
+            [[binary.fetchonly]]
+            url = "{}"
+            sha256 = "{}"
+            "#,
+            url, sha256
+        ),
+    )
+    .unwrap();
+
+    assert!(add(
+        &mut env,
+        &project_dir.path().as_os_str().to_string_lossy(),
+        Some("test")
+    )
+    .is_ok());
+    assert!(fetch(&env, "remote", &temp_copy_dir1, &mut runtime).is_ok());
+    expect_dir_contents(&temp_copy_dir1, &["libc.so.6"]);
+    assert_eq!(
+        fs::read(temp_copy_dir1.path().join("libc.so.6")).unwrap(),
+        CONTENTS
+    );
+
+    // Cached files should not be downloaded again
+    assert!(fetch(&env, "remote", &temp_copy_dir2, &mut runtime).is_ok());
+    expect_dir_contents(&temp_copy_dir2, &["libc.so.6"]);
+    assert_eq!(request_count.load(Ordering::SeqCst), 1);
+
+    assert_matches!(
+        env.cache_manager()
+            .fetch_file(&url, &"0".repeat(64), &mut runtime)
+            .map_err(error_downcast),
+        Err(Ok(SomaError::ChecksumMismatch))
+    );
+}
@@ -1,14 +1,24 @@
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+
 use soma::docker;
 use soma::docker::{
     container_exists, container_from_prob_exists, image_exists, image_from_prob_exists,
     image_from_repo_exists,
 };
-use soma::ops::{add, build, clean, run, stop};
+use soma::ops::{add, build, clean, flag, run, stop, RunOptions};
 
 pub use self::common::*;
 
 mod common;
 
+fn fixed_port(port: u16) -> RunOptions {
+    RunOptions {
+        port: Some(port),
+        ..Default::default()
+    }
+}
+
 #[test]
 fn test_run_stop1() {
     let (_, mut data_dir) = temp_data_dir();
@@ -31,13 +41,14 @@ fn test_run_stop1() {
     assert!(image_from_repo_exists(&images, repo_name));
     assert!(image_from_prob_exists(&images, &problem));
 
-    let container_id = run(&env, prob_query, 31337, &mut runtime).unwrap();
+    let (container_id, port) = run(&env, prob_query, &fixed_port(31337), &mut runtime).unwrap();
+    assert_eq!(port, 31337);
     let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
     assert!(container_exists(&containers, &container_id));
     assert!(container_from_prob_exists(&containers, &problem));
 
     // Problem container should be running exclusively
-    assert!(run(&env, prob_query, 31337, &mut runtime).is_err());
+    assert!(run(&env, prob_query, &fixed_port(31337), &mut runtime).is_err());
 
     // Cleanup
     assert!(stop(&env, prob_query, &mut runtime).is_ok());
@@ -74,13 +85,14 @@ fn test_run_stop2() {
     assert!(image_from_repo_exists(&images, repo_name));
     assert!(image_from_prob_exists(&images, &problem));
 
-    let container_id = run(&env, prob_query, 31338, &mut runtime).unwrap();
+    let (container_id, port) = run(&env, prob_query, &fixed_port(31338), &mut runtime).unwrap();
+    assert_eq!(port, 31338);
     let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
     assert!(container_exists(&containers, &container_id));
     assert!(container_from_prob_exists(&containers, &problem));
 
     // Problem container should be running exclusively
-    assert!(run(&env, prob_query, 31338, &mut runtime).is_err());
+    assert!(run(&env, prob_query, &fixed_port(31338), &mut runtime).is_err());
 
     // Cleanup
     assert!(stop(&env, prob_query, &mut runtime).is_ok());
@@ -94,3 +106,220 @@ fn test_run_stop2() {
     assert!(!image_from_repo_exists(&images, repo_name));
     assert!(!image_from_prob_exists(&images, &problem));
 }
+
+#[test]
+fn test_run_stop_auto_port() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, SIMPLE_BOF_GIT, None).is_ok());
+
+    let prob_query = "simple-bof";
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+
+    let (container_id, port) = run(&env, prob_query, &RunOptions::default(), &mut runtime).unwrap();
+    assert_ne!(port, 0);
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(container_exists(&containers, &container_id));
+    assert!(container_from_prob_exists(&containers, &problem));
+
+    // Cleanup
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+}
+
+#[test]
+fn test_run_stop_mysql() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, "test_repo/php-mysql", Some("web")).is_ok());
+
+    let prob_query = "web.php-mysql";
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+    let image_name = problem.docker_image_name(env.username());
+    let mysql_image_name = problem.docker_service_image_name(env.username(), "mysql");
+
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(image_exists(&images, &image_name));
+    assert!(image_exists(&images, &mysql_image_name));
+
+    let (container_id, _) = run(&env, prob_query, &RunOptions::default(), &mut runtime).unwrap();
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(container_exists(&containers, &container_id));
+    // Both the problem container and the database container belong to the problem
+    assert_eq!(docker::containers_from_prob(containers, &problem).len(), 2);
+
+    // Cleanup
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(!container_from_prob_exists(&containers, &problem));
+
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(!image_exists(&images, &image_name));
+    assert!(!image_exists(&images, &mysql_image_name));
+    assert!(!image_from_prob_exists(&images, &problem));
+}
+
+#[test]
+fn test_run_stop_services() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, "test_repo/web-api", Some("web")).is_ok());
+
+    let prob_query = "web.web-api";
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+    let image_name = problem.docker_image_name(env.username());
+    let api_image_name = problem.docker_service_image_name(env.username(), "api");
+
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(image_exists(&images, &image_name));
+    assert!(image_exists(&images, &api_image_name));
+
+    let (container_id, port) = run(&env, prob_query, &RunOptions::default(), &mut runtime).unwrap();
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(container_exists(&containers, &container_id));
+    assert_eq!(docker::containers_from_prob(containers, &problem).len(), 2);
+
+    // Containers started before a failure should be rolled back
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+    let blocker = TcpListener::bind(("0.0.0.0", port)).unwrap();
+    assert!(run(&env, prob_query, &fixed_port(port), &mut runtime).is_err());
+    drop(blocker);
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(!container_from_prob_exists(&containers, &problem));
+
+    // Cleanup
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(!image_exists(&images, &image_name));
+    assert!(!image_exists(&images, &api_image_name));
+}
+
+#[test]
+fn test_run_stop_isolated() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, SIMPLE_BOF_GIT, None).is_ok());
+
+    let prob_query = "simple-bof";
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+    let proxy_image_name = problem.docker_proxy_image_name(env.username());
+
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+
+    let options = RunOptions {
+        isolated: true,
+        ..Default::default()
+    };
+    let (container_id, port) = run(&env, prob_query, &options, &mut runtime).unwrap();
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(image_exists(&images, &proxy_image_name));
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(container_exists(&containers, &container_id));
+    // The published port is forwarded by a proxy container
+    assert_eq!(docker::containers_from_prob(containers, &problem).len(), 2);
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_ok());
+
+    // Cleanup
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(!image_exists(&images, &proxy_image_name));
+}
+
+#[test]
+fn test_run_stop_flag() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, "test_repo/flag", None).is_ok());
+
+    let prob_query = "flag";
+    assert!(flag(&env, prob_query).is_err());
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+
+    let (_, port) = run(&env, prob_query, &RunOptions::default(), &mut runtime).unwrap();
+    let first_flag = flag(&env, prob_query).unwrap();
+    assert!(first_flag.starts_with("soma{"));
+    let mut received = String::new();
+    TcpStream::connect(("127.0.0.1", port))
+        .unwrap()
+        .read_to_string(&mut received)
+        .unwrap();
+    assert_eq!(received, first_flag);
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+
+    // Each run should have a fresh flag
+    assert!(run(&env, prob_query, &RunOptions::default(), &mut runtime).is_ok());
+    assert_ne!(flag(&env, prob_query).unwrap(), first_flag);
+
+    // Cleanup
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+}
+
+#[test]
+fn test_run_stop_wait() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, SIMPLE_BOF_GIT, None).is_ok());
+    assert!(add(&mut env, "test_repo/healthcheck", None).is_ok());
+
+    let options = RunOptions {
+        wait: true,
+        ..Default::default()
+    };
+
+    // Binary problems are probed on their port by default
+    let prob_query = "simple-bof";
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+    let (container_id, port) = run(&env, prob_query, &options, &mut runtime).unwrap();
+    let health = runtime
+        .block_on(docker::health_status(&env, &container_id))
+        .unwrap();
+    assert_eq!(health, docker::HealthStatus::Healthy);
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_ok());
+    assert!(stop(&env, prob_query, &mut runtime).is_ok());
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+
+    // Containers of an unhealthy problem are rolled back
+    let prob_query = "healthcheck";
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+    assert!(build(&env, prob_query, &mut runtime).is_ok());
+    assert!(run(&env, prob_query, &options, &mut runtime).is_err());
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(!container_from_prob_exists(&containers, &problem));
+
+    // Cleanup
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+}
@@ -0,0 +1,44 @@
+use soma::docker;
+use soma::docker::{container_from_prob_exists, image_exists};
+use soma::ops::{add, clean, verify};
+
+pub use self::common::*;
+
+mod common;
+
+#[test]
+fn test_verify() {
+    let (_, mut data_dir) = temp_data_dir();
+    let mut env = test_env(&mut data_dir);
+    let mut runtime = default_runtime();
+
+    assert!(add(&mut env, "test_repo/solution", None).is_ok());
+    assert!(add(&mut env, SIMPLE_BOF_GIT, None).is_ok());
+
+    let prob_query = "solution";
+    let problem = env
+        .repo_manager()
+        .search_prob(prob_query)
+        .expect("Problem not found");
+
+    // Containers of the problem and the solver should be removed after the verification
+    assert!(verify(&env, prob_query, &mut runtime).is_ok());
+    let containers = runtime.block_on(docker::list_containers(&env)).unwrap();
+    assert!(!container_from_prob_exists(&containers, &problem));
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(image_exists(
+        &images,
+        &problem.docker_solver_image_name(env.username())
+    ));
+
+    // Problems without a solution cannot be verified
+    assert!(verify(&env, "simple-bof", &mut runtime).is_err());
+
+    // Cleanup
+    assert!(clean(&env, prob_query, &mut runtime).is_ok());
+    let images = runtime.block_on(docker::list_images(&env)).unwrap();
+    assert!(!image_exists(
+        &images,
+        &problem.docker_solver_image_name(env.username())
+    ));
+}